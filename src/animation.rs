@@ -0,0 +1,425 @@
+use alloc::vec::Vec;
+
+use limg_core::{PixelEndian, HEADER_SIZE, PIXEL_BYTES};
+
+use crate::image::Image;
+use crate::image_ref::ImageRef;
+use crate::pixel::Pixel;
+use crate::error::{Error, Result};
+
+/// アニメーションのシグネチャ
+const SIGNATURE: [u8; 4] = *b"LIMA";
+
+/// 対応しているアニメーションのバージョン
+const VERSION: u8 = 1;
+
+/// 透明色が指定されていることを示すフラグ
+const FLAG_TRANSPARENT: u8 = 0b10;
+
+/// アニメーションのヘッダーのサイズ
+/// 
+/// シグネチャ(4)、バージョン(1)、フラグ(1)、幅(2)、高さ(2)、透明色(2)、フレーム数(2)、ループ回数(2)の順に並びます。
+/// 数値はすべてリトルエンディアンです。
+const ANIMATION_HEADER_SIZE: usize = 16;
+
+/// フレームごとの表示時間のサイズ
+const DURATION_SIZE: usize = 2;
+
+/// アニメーションの1フレームです。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// フレームの画像
+    image: Image,
+
+    /// 表示時間(ミリ秒)
+    duration: u16,
+}
+
+impl Frame {
+    /// フレームの画像を返します。
+    #[inline(always)]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// フレームの表示時間をミリ秒で返します。
+    #[inline(always)]
+    pub fn duration(&self) -> u16 {
+        self.duration
+    }
+
+    /// フレームの表示時間をミリ秒で設定します。
+    #[inline(always)]
+    pub fn set_duration(&mut self, duration: u16) {
+        self.duration = duration;
+    }
+
+    /// フレームを破棄して画像を返します。
+    #[inline(always)]
+    pub fn into_image(self) -> Image {
+        self.image
+    }
+}
+
+/// 同じ大きさと透明色を持つ画像を連続して表示するアニメーションです。
+/// 
+/// エンコード時は16バイトのヘッダーと各フレームの表示時間の後に、各フレームのLimg画像が連続して並びます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Animation, Image, Pixel, Result};
+/// # fn main() -> Result<()> {
+/// let mut animation = Animation::new(8, 8);
+/// for color in [Pixel::RED, Pixel::GREEN, Pixel::BLUE] {
+///     let mut image = Image::new(8, 8);
+///     image.fill(color);
+///     animation.push_frame(image, 100)?;
+/// }
+/// 
+/// let mut buf = vec![0u8; animation.encoded_size()];
+/// animation.to_buffer(&mut buf)?;
+/// 
+/// let decoded = Animation::from_buffer(&buf)?;
+/// assert_eq!(decoded, animation);
+/// assert_eq!(decoded.total_duration(), 300);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Animation {
+    /// フレームの幅
+    width: u16,
+
+    /// フレームの高さ
+    height: u16,
+
+    /// フレームの透明色
+    transparent_color: Option<Pixel>,
+
+    /// ループ回数
+    /// 
+    /// `0`の場合は無限にループします。
+    loop_count: u16,
+
+    /// フレーム
+    frames: Vec<Frame>,
+}
+
+impl Animation {
+    /// `width`と`height`を指定して、フレームのない透明色なしのアニメーションを作成します。
+    /// 
+    /// ループ回数は`0`(無限)になります。
+    #[inline]
+    pub fn new(width: u16, height: u16) -> Animation {
+        Animation { width, height, transparent_color: None, loop_count: 0, frames: Vec::new() }
+    }
+
+    /// `width`と`height`、`transparent_color`を指定して、フレームのないアニメーションを作成します。
+    #[inline]
+    pub fn with_transparent_color(width: u16, height: u16, transparent_color: Pixel) -> Animation {
+        Animation { transparent_color: Some(transparent_color), ..Animation::new(width, height) }
+    }
+
+    /// フレームの幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// フレームの高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// フレームの透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    #[inline(always)]
+    pub fn transparent_color(&self) -> Option<Pixel> {
+        self.transparent_color
+    }
+
+    /// ループ回数を返します。
+    /// 
+    /// `0`の場合は無限にループします。
+    #[inline(always)]
+    pub fn loop_count(&self) -> u16 {
+        self.loop_count
+    }
+
+    /// ループ回数を設定します。
+    /// 
+    /// `0`の場合は無限にループします。
+    #[inline(always)]
+    pub fn set_loop_count(&mut self, loop_count: u16) {
+        self.loop_count = loop_count;
+    }
+
+    /// フレーム数を返します。
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// フレームがない場合`true`を返します。
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// `index`番目のフレームを返します。
+    #[inline(always)]
+    pub fn frame(&self, index: usize) -> Option<&Frame> {
+        self.frames.get(index)
+    }
+
+    /// `index`番目のフレームの可変参照を返します。
+    #[inline(always)]
+    pub fn frame_mut(&mut self, index: usize) -> Option<&mut Frame> {
+        self.frames.get_mut(index)
+    }
+
+    /// 先頭から順にフレームを返す`Iterator`を返します。
+    #[inline(always)]
+    pub fn frames(&self) -> core::slice::Iter<'_, Frame> {
+        self.frames.iter()
+    }
+
+    /// 1ループにかかる時間をミリ秒で返します。
+    pub fn total_duration(&self) -> u32 {
+        self.frames.iter().map(|frame| frame.duration as u32).sum()
+    }
+
+    /// 開始から`time`ミリ秒後に表示するフレームを返します。
+    /// 
+    /// ループ回数を超えた場合は最後のフレームを返します。フレームがない場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Animation, Image, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut animation = Animation::new(1, 1);
+    /// animation.push_frame(Image::new(1, 1), 100)?;
+    /// animation.push_frame(Image::new(1, 1), 50)?;
+    /// 
+    /// assert_eq!(animation.frame_index_at(120), Some(1));
+    /// assert_eq!(animation.frame_index_at(170), Some(0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn frame_index_at(&self, time: u64) -> Option<usize> {
+        if self.frames.is_empty() {
+            return None;
+        }
+
+        let total = self.total_duration() as u64;
+        if total == 0 || (self.loop_count != 0 && time >= total * self.loop_count as u64) {
+            return Some(self.frames.len() - 1);
+        }
+
+        let mut time = time % total;
+        for (i, frame) in self.frames.iter().enumerate() {
+            if time < frame.duration as u64 {
+                return Some(i);
+            }
+            time -= frame.duration as u64;
+        }
+
+        Some(self.frames.len() - 1)
+    }
+
+    /// 表示時間`duration`ミリ秒のフレームを末尾に追加します。
+    /// 
+    /// # Errors
+    /// 
+    /// `image`の大きさか透明色がアニメーションと異なる場合、`Error::FrameMismatch`を返します。
+    pub fn push_frame(&mut self, image: Image, duration: u16) -> Result<()> {
+        if image.width() != self.width || image.height() != self.height || image.transparent_color() != self.transparent_color {
+            return Err(Error::FrameMismatch);
+        }
+
+        self.frames.push(Frame { image, duration });
+        Ok(())
+    }
+
+    /// `index`番目のフレームを取り除いて返します。
+    /// 
+    /// # Panics
+    /// 
+    /// `index`がフレーム数以上の場合パニックします。
+    #[inline]
+    pub fn remove_frame(&mut self, index: usize) -> Frame {
+        self.frames.remove(index)
+    }
+
+    /// エンコードした場合のバイト数を返します。
+    pub fn encoded_size(&self) -> usize {
+        ANIMATION_HEADER_SIZE + self.frames.len() * (DURATION_SIZE + self.frame_size())
+    }
+
+    /// エンコードされた1フレームのバイト数を返します。
+    #[inline(always)]
+    fn frame_size(&self) -> usize {
+        HEADER_SIZE + self.width as usize * self.height as usize * PIXEL_BYTES
+    }
+
+    /// アニメーションをエンコードし`buf`に書き込みます。
+    /// 
+    /// ピクセルはリトルエンディアンで書き込まれます。
+    /// 
+    /// # Errors
+    /// 
+    /// フレームの大きさが0か`buf`の長さが[`encoded_size`]より短い場合、`Error`を返します。
+    /// 
+    /// [`encoded_size`]: Animation::encoded_size
+    #[inline(always)]
+    pub fn to_buffer(&self, buf: &mut impl AsMut<[u8]>) -> Result<()> {
+        self.to_buffer_with_endian(buf, PixelEndian::Little)
+    }
+
+    /// アニメーションを指定された`endian`でピクセルエンコードし`buf`に書き込みます。
+    /// 
+    /// # Errors
+    /// 
+    /// フレームの大きさが0か`buf`の長さが[`encoded_size`]より短い場合、`Error`を返します。
+    /// 
+    /// [`encoded_size`]: Animation::encoded_size
+    pub fn to_buffer_with_endian(&self, buf: &mut impl AsMut<[u8]>, endian: PixelEndian) -> Result<()> {
+        let buf = buf.as_mut();
+        if self.width == 0 || self.height == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+        if buf.len() < self.encoded_size() {
+            return Err(Error::OutputBufferTooSmall);
+        }
+        let frame_count = u16::try_from(self.frames.len()).map_err(|_| Error::SizeMismatch)?;
+
+        let (header, rest) = buf.split_at_mut(ANIMATION_HEADER_SIZE);
+        header[0..4].copy_from_slice(&SIGNATURE);
+        header[4] = VERSION;
+        header[5] = if self.transparent_color.is_some() { FLAG_TRANSPARENT } else { 0 };
+        header[6..8].copy_from_slice(&self.width.to_le_bytes());
+        header[8..10].copy_from_slice(&self.height.to_le_bytes());
+        header[10..12].copy_from_slice(&self.transparent_color.map_or(0, |p| p.0).to_le_bytes());
+        header[12..14].copy_from_slice(&frame_count.to_le_bytes());
+        header[14..16].copy_from_slice(&self.loop_count.to_le_bytes());
+
+        let (durations, rest) = rest.split_at_mut(self.frames.len() * DURATION_SIZE);
+        for (bytes, frame) in durations.chunks_exact_mut(DURATION_SIZE).zip(&self.frames) {
+            bytes.copy_from_slice(&frame.duration.to_le_bytes());
+        }
+
+        for (mut bytes, frame) in rest.chunks_exact_mut(self.frame_size()).zip(&self.frames) {
+            frame.image.to_buffer_with_endian(&mut bytes, endian)?;
+        }
+
+        Ok(())
+    }
+
+    /// `buf`からアニメーションを読み取ります。
+    /// 
+    /// # Errors
+    /// 
+    /// データが不正な場合、`Error`を返します。
+    /// フレームの大きさか透明色がヘッダーと異なる場合、`Error::FrameMismatch`を返します。
+    pub fn from_buffer(buf: impl AsRef<[u8]>) -> Result<Animation> {
+        let buf = buf.as_ref();
+        if buf.len() < ANIMATION_HEADER_SIZE {
+            return Err(Error::InputBufferTooSmall);
+        }
+        if buf[0..4] != SIGNATURE || buf[4] != VERSION || buf[5] & !FLAG_TRANSPARENT != 0 {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        let read_u16 = |i: usize| u16::from_le_bytes([buf[i], buf[i + 1]]);
+        let width = read_u16(6);
+        let height = read_u16(8);
+        if width == 0 || height == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+        let transparent_color = if buf[5] & FLAG_TRANSPARENT != 0 { Some(Pixel(read_u16(10))) } else { None };
+        let frame_count = read_u16(12) as usize;
+        let loop_count = read_u16(14);
+
+        let mut animation = Animation { width, height, transparent_color, loop_count, frames: Vec::with_capacity(frame_count) };
+        let frame_size = animation.frame_size();
+
+        let durations = buf.get(ANIMATION_HEADER_SIZE..ANIMATION_HEADER_SIZE + frame_count * DURATION_SIZE).ok_or(Error::InputBufferTooSmall)?;
+        let mut offset = ANIMATION_HEADER_SIZE + durations.len();
+        for duration in durations.chunks_exact(DURATION_SIZE) {
+            let data = buf.get(offset..offset + frame_size).ok_or(Error::InputBufferTooSmall)?;
+            let image = ImageRef::new(data)?;
+            if image.width() != width || image.height() != height || image.transparent_color() != transparent_color {
+                return Err(Error::FrameMismatch);
+            }
+
+            animation.frames.push(Frame { image: image.to_image(), duration: u16::from_le_bytes([duration[0], duration[1]]) });
+            offset += frame_size;
+        }
+
+        Ok(animation)
+    }
+}
+
+impl<'a> IntoIterator for &'a Animation {
+    type Item = &'a Frame;
+    type IntoIter = core::slice::Iter<'a, Frame>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.frames()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Animation {
+    /// `path`からアニメーションを読み取ります。
+    /// 
+    /// # Errors
+    /// 
+    /// データが不正かIO操作に失敗した場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,no_run
+    /// # use limg::{Animation, Result};
+    /// # fn main() -> Result<()> {
+    /// let animation = Animation::open("status.lima")?;
+    /// for frame in animation.frames() {
+    ///     println!("{}ms", frame.duration());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Animation> {
+        Animation::from_buffer(std::fs::read(path)?)
+    }
+
+    /// アニメーションをエンコードし`path`に保存します。既にファイルが存在する場合上書きします。
+    /// 
+    /// ピクセルはリトルエンディアンで書き込まれます。
+    /// 
+    /// # Errors
+    /// 
+    /// フレームの大きさが0かIO操作に失敗した場合、`Error`を返します。
+    #[inline(always)]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.save_with_endian(path, PixelEndian::Little)
+    }
+
+    /// アニメーションを指定された`endian`でピクセルエンコードし`path`に保存します。既にファイルが存在する場合上書きします。
+    /// 
+    /// # Errors
+    /// 
+    /// フレームの大きさが0かIO操作に失敗した場合、`Error`を返します。
+    pub fn save_with_endian(&self, path: impl AsRef<std::path::Path>, endian: PixelEndian) -> Result<()> {
+        let mut buf = alloc::vec![0u8; self.encoded_size()];
+        self.to_buffer_with_endian(&mut buf, endian)?;
+        std::fs::write(path, buf)?;
+        Ok(())
+    }
+}
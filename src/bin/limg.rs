@@ -0,0 +1,479 @@
+//! Limg画像を操作するコマンドラインツールです。
+//! 
+//! `cli`機能を有効にするとビルドされます。
+//! 
+//! ```text
+//! limg info FILES...
+//! limg convert [-o OUTPUT | --to FORMAT] [--out-dir DIR] [--endian ENDIAN] [--compress METHOD] FILES...
+//! limg swap-endian [-o OUTPUT | --out-dir DIR] FILES...
+//! limg set-key COLOR [-o OUTPUT | --out-dir DIR] FILES...
+//! limg resize WIDTHxHEIGHT [--filter FILTER] [-o OUTPUT | --out-dir DIR] FILES...
+//! limg crop X,Y,WIDTH,HEIGHT [-o OUTPUT | --out-dir DIR] FILES...
+//! limg validate FILES...
+//! ```
+//! 
+//! `FILES`には`*`と`?`を含むパターンを指定できます。
+//! 出力先を指定しない場合、`convert`以外は入力ファイルを上書きします。
+//! 
+//! 終了コードは、すべてのファイルの処理に成功した場合`0`、処理に失敗したファイルがある場合`1`、引数が不正な場合`2`です。
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+
+use limg::{Animation, Compression, DecodeOptions, Filter, GenericImage, Image, IndexedImage, LimgDecoder, Pixel, PixelEndian, PnmFormat, Rect};
+
+const USAGE: &str = "\
+使い方: limg <COMMAND> [OPTIONS] FILES...
+
+コマンド:
+    info                        ヘッダーから幅、高さ、エンディアン、透明色、圧縮方式を表示します
+    convert                     Limg、PNG、BMP、PPM、PAMの間で変換します
+    swap-endian                 ピクセルのエンディアンを反転します
+    set-key COLOR               透明色を設定します (#RRGGBB、0xRGB565、none)
+    resize WIDTHxHEIGHT         拡大縮小します
+    crop X,Y,WIDTH,HEIGHT       矩形領域を切り抜きます
+    validate                    画像をデコードできるか厳密に検証します
+
+オプション:
+    -o, --output PATH           出力ファイル (入力が1つの場合のみ)
+        --out-dir DIR           出力ディレクトリ
+        --to FORMAT             convertの出力形式 (limg、png、bmp、ppm、pam)
+        --endian ENDIAN         Limgのピクセルのエンディアン (little、big)
+        --compress METHOD       Limgの圧縮方式 (none、rle16、lz4)
+        --filter FILTER         resizeの補間フィルター (nearest、bilinear、bicubic、lanczos3)
+    -h, --help                  このヘルプを表示します
+
+終了コード: 0 成功、1 処理に失敗したファイルがある、2 引数が不正";
+
+/// ファイルごとの処理で発生したエラー
+type FileResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// ファイルごとの処理
+type Process<'a> = Box<dyn Fn(&Path) -> FileResult<()> + 'a>;
+
+/// 引数の誤り
+struct UsageError(String);
+
+/// 読み書きできる画像形式
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Limg,
+    Png,
+    Bmp,
+    Pnm(PnmFormat),
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Format> {
+        match name.to_ascii_lowercase().as_str() {
+            "limg" => Some(Format::Limg),
+            "png" => Some(Format::Png),
+            "bmp" => Some(Format::Bmp),
+            "ppm" | "pnm" => Some(Format::Pnm(PnmFormat::Ppm)),
+            "pam" => Some(Format::Pnm(PnmFormat::Pam)),
+            _ => None,
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Format> {
+        path.extension().and_then(|ext| ext.to_str()).and_then(Format::from_name)
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Limg => "limg",
+            Format::Png => "png",
+            Format::Bmp => "bmp",
+            Format::Pnm(PnmFormat::Pam) => "pam",
+            Format::Pnm(_) => "ppm",
+        }
+    }
+}
+
+/// 解析したコマンドライン引数
+#[derive(Default)]
+struct Args {
+    command: String,
+    operand: Option<String>,
+    files: Vec<PathBuf>,
+    output: Option<PathBuf>,
+    out_dir: Option<PathBuf>,
+    to: Option<Format>,
+    endian: Option<PixelEndian>,
+    compression: Option<Option<Compression>>,
+    filter: Filter,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, UsageError> {
+        let mut args = args.into_iter();
+        let mut parsed = Args { command: args.next().ok_or_else(|| usage("コマンドが指定されていません"))?, ..Args::default() };
+        if parsed.command == "-h" || parsed.command == "--help" {
+            return Ok(parsed);
+        }
+
+        let mut patterns = Vec::new();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| usage(format!("{name}に値が指定されていません")));
+            match arg.as_str() {
+                "-h" | "--help" => parsed.command = arg,
+                "-o" | "--output" => parsed.output = Some(PathBuf::from(value(&arg)?)),
+                "--out-dir" => parsed.out_dir = Some(PathBuf::from(value(&arg)?)),
+                "--to" => {
+                    let name = value(&arg)?;
+                    parsed.to = Some(Format::from_name(&name).ok_or_else(|| usage(format!("不明な形式です: {name}")))?);
+                },
+                "--endian" => parsed.endian = Some(match value(&arg)?.as_str() {
+                    "little" | "le" => PixelEndian::Little,
+                    "big" | "be" => PixelEndian::Big,
+                    name => return Err(usage(format!("不明なエンディアンです: {name}"))),
+                }),
+                "--compress" => parsed.compression = Some(match value(&arg)?.as_str() {
+                    "none" => None,
+                    "rle16" | "rle" => Some(Compression::Rle16),
+                    "lz4" | "lz" => Some(Compression::Lz4Like),
+                    name => return Err(usage(format!("不明な圧縮方式です: {name}"))),
+                }),
+                "--filter" => parsed.filter = match value(&arg)?.as_str() {
+                    "nearest" => Filter::Nearest,
+                    "bilinear" => Filter::Bilinear,
+                    "bicubic" => Filter::Bicubic,
+                    "lanczos3" => Filter::Lanczos3,
+                    name => return Err(usage(format!("不明なフィルターです: {name}"))),
+                },
+                _ if arg.starts_with('-') && arg.len() > 1 => return Err(usage(format!("不明なオプションです: {arg}"))),
+                _ => patterns.push(arg),
+            }
+        }
+
+        if matches!(parsed.command.as_str(), "set-key" | "resize" | "crop") {
+            if patterns.is_empty() {
+                return Err(usage(format!("{}の引数が指定されていません", parsed.command)));
+            }
+            parsed.operand = Some(patterns.remove(0));
+        }
+
+        for pattern in &patterns {
+            parsed.files.extend(expand_glob(pattern)?);
+        }
+        if parsed.files.is_empty() && !matches!(parsed.command.as_str(), "-h" | "--help") {
+            return Err(usage("ファイルが指定されていません"));
+        }
+        if parsed.output.is_some() && (parsed.files.len() > 1 || parsed.out_dir.is_some()) {
+            return Err(usage("--outputは入力が1つの場合のみ指定でき、--out-dirと同時には指定できません"));
+        }
+
+        Ok(parsed)
+    }
+
+    /// `input`の出力先を返します。
+    /// 
+    /// `extension`を指定した場合、入力ファイル名の拡張子を置き換えます。
+    fn output_path(&self, input: &Path, extension: Option<&str>) -> PathBuf {
+        if let Some(output) = &self.output {
+            return output.clone();
+        }
+
+        let mut path = match (&self.out_dir, input.file_name()) {
+            (Some(dir), Some(name)) => dir.join(name),
+            _ => input.to_path_buf(),
+        };
+        if let Some(extension) = extension {
+            path.set_extension(extension);
+        }
+        path
+    }
+}
+
+#[inline]
+fn usage(message: impl Into<String>) -> UsageError {
+    UsageError(message.into())
+}
+
+/// `pattern`に一致するパスを名前順に返します。
+/// 
+/// `*`と`?`を含まないパスはそのまま返します。
+fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, UsageError> {
+    if !pattern.contains(['*', '?']) {
+        return Ok(vec![PathBuf::from(pattern)]);
+    }
+
+    let mut paths = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let name = match component {
+            Component::Normal(name) => name.to_string_lossy(),
+            _ => {
+                paths.iter_mut().for_each(|path| path.push(component));
+                continue;
+            }
+        };
+
+        if !name.contains(['*', '?']) {
+            paths.iter_mut().for_each(|path| path.push(&*name));
+            continue;
+        }
+
+        let pattern: Vec<char> = name.chars().collect();
+        let mut matched = Vec::new();
+        for dir in &paths {
+            let entries = match std::fs::read_dir(if dir.as_os_str().is_empty() { Path::new(".") } else { dir }) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let file_name: Vec<char> = entry.file_name().to_string_lossy().chars().collect();
+                // 隠しファイルは`.`から始まるパターンにのみ一致させる
+                if file_name.first() == Some(&'.') && pattern.first() != Some(&'.') {
+                    continue;
+                }
+                if wildcard_match(&pattern, &file_name) {
+                    matched.push(dir.join(entry.file_name()));
+                }
+            }
+        }
+        paths = matched;
+    }
+
+    paths.retain(|path| path.exists());
+    paths.sort();
+    if paths.is_empty() {
+        return Err(usage(format!("パターンに一致するファイルがありません: {pattern}")));
+    }
+    Ok(paths)
+}
+
+/// `*`と`?`を含むパターンが`name`全体に一致するかを返します。
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            },
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            _ => match backtrack {
+                Some((star, start)) => {
+                    backtrack = Some((star, start + 1));
+                    p = star + 1;
+                    n = start + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 保存時に維持するLimg画像のエンコード設定
+#[derive(Clone, Copy)]
+struct Encoding {
+    endian: PixelEndian,
+    compression: Option<Compression>,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding { endian: PixelEndian::Little, compression: None }
+    }
+}
+
+/// Limg画像を読み込み、画像とエンコード設定を返します。
+fn open_limg(path: &Path) -> FileResult<(Image, Encoding)> {
+    let decoder = LimgDecoder::new(BufReader::new(File::open(path)?))?;
+    let encoding = Encoding { endian: decoder.spec().pixel_endian, compression: decoder.compression() };
+    Ok((decoder.read_image()?, encoding))
+}
+
+/// 拡張子から形式を判別して画像を読み込みます。
+/// 
+/// 判別できない場合はLimg画像として読み込みます。
+fn open_any(path: &Path) -> FileResult<(Image, Encoding)> {
+    let image = match Format::from_path(path).unwrap_or(Format::Limg) {
+        Format::Limg => return open_limg(path),
+        Format::Png => Image::from_png(BufReader::new(File::open(path)?))?,
+        Format::Bmp => Image::from_bmp(std::fs::read(path)?)?,
+        Format::Pnm(_) => Image::from_pnm(std::fs::read(path)?)?,
+    };
+    Ok((image, Encoding::default()))
+}
+
+fn save_limg(image: &Image, path: &Path, encoding: Encoding) -> FileResult<()> {
+    match encoding.compression {
+        Some(compression) => image.save_compressed_with_endian(path, compression, encoding.endian)?,
+        None => image.save_with_endian(path, encoding.endian)?,
+    }
+    Ok(())
+}
+
+fn save_as(image: &Image, path: &Path, format: Format, encoding: Encoding) -> FileResult<()> {
+    match format {
+        Format::Limg => return save_limg(image, path, encoding),
+        Format::Png => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            image.to_png(&mut writer)?;
+            writer.flush()?;
+        },
+        Format::Bmp => {
+            let mut buf = vec![0u8; image.bmp_size()];
+            image.to_bmp(&mut buf)?;
+            std::fs::write(path, buf)?;
+        },
+        Format::Pnm(pnm) => {
+            let mut buf = vec![0u8; image.pnm_size(pnm)];
+            let size = image.to_pnm(&mut buf, pnm)?;
+            std::fs::write(path, &buf[..size])?;
+        },
+    }
+    Ok(())
+}
+
+fn parse_color(text: &str) -> Result<Option<Pixel>, UsageError> {
+    let invalid = || usage(format!("不正な色です: {text}"));
+    if text == "none" {
+        Ok(None)
+    } else if let Some(hex) = text.strip_prefix('#') {
+        let rgb = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6).ok_or_else(invalid)?;
+        Ok(Some(Pixel::from_rgb([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])))
+    } else if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Ok(Some(Pixel::new(u16::from_str_radix(hex, 16).map_err(|_| invalid())?)))
+    } else {
+        Err(invalid())
+    }
+}
+
+fn parse_size(text: &str) -> Result<(u16, u16), UsageError> {
+    text.split_once(['x', 'X'])
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .ok_or_else(|| usage(format!("不正なサイズです: {text}")))
+}
+
+fn parse_rect(text: &str) -> Result<Rect, UsageError> {
+    let values: Vec<u16> = text.split(',').map(|v| v.trim().parse()).collect::<Result<_, _>>()
+        .map_err(|_| usage(format!("不正な矩形です: {text}")))?;
+    match values[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok(Rect::new(x, y, width, height)),
+        _ => Err(usage(format!("不正な矩形です: {text}"))),
+    }
+}
+
+fn info(path: &Path) -> FileResult<()> {
+    let info = limg::probe(path)?;
+    let endian = if matches!(info.pixel_endian(), PixelEndian::Big) { "big" } else { "little" };
+    let key = info.transparent_color().map_or_else(|| "none".to_string(), |key| format!("0x{:04X}", key.0));
+    let compression = match info.compression() {
+        None => "none",
+        Some(Compression::Rle16) => "rle16",
+        Some(Compression::Lz4Like) => "lz4",
+    };
+    print!("{}: width={} height={} endian={endian} key={key} compression={compression}", path.display(), info.width(), info.height());
+    if info.is_truncated() {
+        print!(" truncated");
+    } else if info.has_trailing_bytes() {
+        print!(" trailing-bytes");
+    }
+    println!();
+    Ok(())
+}
+
+/// 画像全体をデコードできるか検証します。
+/// 
+/// `.lima`はアニメーション、`.limi`はパレット画像として検証します。
+/// Limg画像は画像データの後の余分なデータも不正とします。
+fn validate(path: &Path) -> FileResult<()> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("lima") => drop(Animation::open(path)?),
+        Some("limi") => drop(IndexedImage::open(path)?),
+        _ => drop(Image::from_buffer_with_options(std::fs::read(path)?, DecodeOptions::STRICT)?),
+    }
+    println!("{}: ok", path.display());
+    Ok(())
+}
+
+fn run(args: Args) -> Result<bool, UsageError> {
+    let args = &args;
+    let process: Process = match args.command.as_str() {
+        "-h" | "--help" => {
+            println!("{USAGE}");
+            return Ok(true);
+        },
+        "info" => Box::new(info),
+        "validate" => Box::new(validate),
+        "convert" => {
+            let format = match (args.to, &args.output) {
+                (Some(format), _) => format,
+                (None, Some(output)) => Format::from_path(output).ok_or_else(|| usage("出力ファイルの拡張子から形式を判別できません"))?,
+                (None, None) => return Err(usage("convertには--toか--outputが必要です")),
+            };
+            Box::new(move |path| {
+                let (image, encoding) = open_any(path)?;
+                let encoding = Encoding {
+                    endian: args.endian.unwrap_or(encoding.endian),
+                    compression: args.compression.unwrap_or(encoding.compression),
+                };
+                save_as(&image, &args.output_path(path, Some(format.extension())), format, encoding)
+            })
+        },
+        "swap-endian" => Box::new(move |path| {
+            let (image, mut encoding) = open_limg(path)?;
+            encoding.endian = if matches!(encoding.endian, PixelEndian::Big) { PixelEndian::Little } else { PixelEndian::Big };
+            save_limg(&image, &args.output_path(path, None), encoding)
+        }),
+        "set-key" => {
+            let key = parse_color(args.operand.as_deref().unwrap_or_default())?;
+            Box::new(move |path| {
+                let (mut image, encoding) = open_limg(path)?;
+                image.set_transparent_color(key);
+                save_limg(&image, &args.output_path(path, None), encoding)
+            })
+        },
+        "resize" => {
+            let (width, height) = parse_size(args.operand.as_deref().unwrap_or_default())?;
+            Box::new(move |path| {
+                let (image, encoding) = open_limg(path)?;
+                save_limg(&image.resize(width, height, args.filter), &args.output_path(path, None), encoding)
+            })
+        },
+        "crop" => {
+            let rect = parse_rect(args.operand.as_deref().unwrap_or_default())?;
+            Box::new(move |path| {
+                let (image, encoding) = open_limg(path)?;
+                let view = image.view(rect).ok_or("切り抜く矩形が画像の範囲外です")?;
+                save_limg(&view.to_image(), &args.output_path(path, None), encoding)
+            })
+        },
+        command => return Err(usage(format!("不明なコマンドです: {command}"))),
+    };
+
+    if let Some(dir) = &args.out_dir && let Err(err) = std::fs::create_dir_all(dir) {
+        eprintln!("limg: {}: {err}", dir.display());
+        return Ok(false);
+    }
+
+    let mut success = true;
+    for path in &args.files {
+        if let Err(err) = process(path) {
+            eprintln!("limg: {}: {err}", path.display());
+            success = false;
+        }
+    }
+    Ok(success)
+}
+
+fn main() -> ExitCode {
+    match Args::parse(std::env::args().skip(1)).and_then(run) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(UsageError(message)) => {
+            eprintln!("limg: {message}\n\n{USAGE}");
+            ExitCode::from(2)
+        },
+    }
+}
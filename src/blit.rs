@@ -0,0 +1,101 @@
+use crate::generic::{GenericImage, GenericImageMut};
+use crate::image::Image;
+use crate::rect::Rect;
+
+/// `src`の`region`の範囲を`dst`の`(dst_x, dst_y)`に描画します。
+/// 
+/// [`GenericImageMut::blit_region`]の実装です。
+pub(crate) fn blit_region<D, S>(dst: &mut D, src: &S, region: Rect, dst_x: i32, dst_y: i32)
+where
+    D: GenericImageMut + ?Sized,
+    S: GenericImage + ?Sized,
+{
+    let Some(region) = region.intersection(&Rect::new(0, 0, src.width(), src.height())) else {
+        return;
+    };
+
+    // 描画先の範囲で切り取る
+    // `i32`の範囲の端でも溢れないように`i64`で計算する
+    let (mut sx, mut sy) = (region.x as i64, region.y as i64);
+    let (mut width, mut height) = (region.width as i64, region.height as i64);
+    let (mut dx, mut dy) = (dst_x as i64, dst_y as i64);
+    if dx < 0 {
+        sx -= dx;
+        width += dx;
+        dx = 0;
+    }
+    if dy < 0 {
+        sy -= dy;
+        height += dy;
+        dy = 0;
+    }
+    width = width.min(dst.width() as i64 - dx);
+    height = height.min(dst.height() as i64 - dy);
+    if width <= 0 || height <= 0 {
+        return;
+    }
+
+    let (sx, dx, width) = (sx as usize, dx as usize, width as usize);
+
+    for row in 0..height {
+        let src_row = &src.row((sy + row) as u16)[sx..sx + width];
+        let dst_row = &mut dst.row_mut((dy + row) as u16)[dx..dx + width];
+
+        match src.transparent_color() {
+            None => dst_row.copy_from_slice(src_row),
+            Some(key) => {
+                for (dst, &pixel) in dst_row.iter_mut().zip(src_row) {
+                    if pixel != key {
+                        *dst = pixel;
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl Image {
+    /// `src`を`(dst_x, dst_y)`を左上隅として画像に描画します。
+    /// 
+    /// [`GenericImageMut::blit`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::new(4, 4);
+    /// let mut sprite = Image::with_transparent_color(2, 2, Pixel::MAGENTA);
+    /// sprite.fill(Pixel::WHITE);
+    /// sprite[(0, 0)] = Pixel::MAGENTA;
+    /// 
+    /// image.blit(&sprite, -1, 3);
+    /// 
+    /// assert_eq!(image[(0, 3)], Pixel::WHITE);
+    /// assert_eq!(image[(1, 3)], Pixel::BLACK);
+    /// ```
+    #[inline(always)]
+    pub fn blit(&mut self, src: &impl GenericImage, dst_x: i32, dst_y: i32) {
+        self.blit_region(src, Rect::new(0, 0, src.width(), src.height()), dst_x, dst_y);
+    }
+
+    /// `src`の`region`の範囲を`(dst_x, dst_y)`を左上隅として画像に描画します。
+    /// 
+    /// [`GenericImageMut::blit_region`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Rect};
+    /// let mut image = Image::new(4, 4);
+    /// let mut sheet = Image::new(8, 8);
+    /// sheet[(4, 4)] = Pixel::RED;
+    /// 
+    /// image.blit_region(&sheet, Rect::new(4, 4, 4, 4), 0, 0);
+    /// 
+    /// assert_eq!(image[(0, 0)], Pixel::RED);
+    /// ```
+    #[inline(always)]
+    pub fn blit_region(&mut self, src: &impl GenericImage, region: Rect, dst_x: i32, dst_y: i32) {
+        blit_region(self, src, region, dst_x, dst_y);
+    }
+}
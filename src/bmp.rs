@@ -0,0 +1,301 @@
+use alloc::vec::Vec;
+
+use crate::image::Image;
+use crate::pixel::{Pixel, key_transparent_pixels};
+use crate::error::{Error, Result};
+
+/// ファイルヘッダーのサイズ
+const FILE_HEADER_SIZE: usize = 14;
+/// `BITMAPINFOHEADER`のサイズ
+const INFO_HEADER_SIZE: usize = 40;
+/// RGB565のビットマスクのサイズ
+const MASKS_SIZE: usize = 12;
+/// 書き込み時のピクセルデータの開始位置
+const PIXEL_OFFSET: usize = FILE_HEADER_SIZE + INFO_HEADER_SIZE + MASKS_SIZE;
+
+/// 無圧縮
+const BI_RGB: u32 = 0;
+/// ビットフィールド
+const BI_BITFIELDS: u32 = 3;
+
+/// 72DPI相当の解像度(pixel/m)
+const RESOLUTION: i32 = 2835;
+
+#[inline(always)]
+fn read_u16(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+#[inline(always)]
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([buf[offset], buf[offset + 1], buf[offset + 2], buf[offset + 3]])
+}
+
+#[inline(always)]
+const fn row_stride(width: usize, bits_per_pixel: usize) -> usize {
+    (width * bits_per_pixel).div_ceil(32) * 4
+}
+
+/// `mask`の立っているビットが連続している場合`true`を返します。
+/// 
+/// [`channel`]は連続したマスクのみを想定しています。
+#[inline(always)]
+fn is_contiguous(mask: u32) -> bool {
+    let bits = mask.checked_shr(mask.trailing_zeros()).unwrap_or(0);
+    bits & bits.wrapping_add(1) == 0
+}
+
+/// `mask`で指定されたチャンネルを8ビットに変換して取り出します。
+/// 
+/// `mask`のビットは連続している必要があります。
+fn channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+
+    let shift = mask.trailing_zeros();
+    let bits = (mask >> shift).count_ones();
+    let v = (value & mask) >> shift;
+
+    if bits >= 8 {
+        (v >> (bits - 8)) as u8
+    } else {
+        let max = (1u32 << bits) - 1;
+        ((v * 255 + max / 2) / max) as u8
+    }
+}
+
+/// ピクセルの読み取り方法
+enum Layout {
+    /// RGB565のビットフィールド
+    Rgb565,
+    /// 任意のビットフィールド
+    Masks { r: u32, g: u32, b: u32, a: u32 },
+    /// BGR順の24ビット
+    Bgr24,
+}
+
+impl Image {
+    /// [`to_bmp`]で書き込まれるBMP画像のバイト数を返します。
+    /// 
+    /// 4GiB以上になる場合、[`to_bmp`]はエラーを返します。
+    /// 
+    /// [`to_bmp`]: Image::to_bmp
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Image;
+    /// let image = Image::new(3, 2);
+    /// 
+    /// assert_eq!(image.bmp_size(), 66 + 8 * 2);
+    /// ```
+    pub fn bmp_size(&self) -> usize {
+        PIXEL_OFFSET + row_stride(self.width() as usize, 16) * self.height() as usize
+    }
+
+    /// 画像を16ビットのBI_BITFIELDS形式のBMP画像にエンコードし`buf`に書き込みます。
+    /// 
+    /// ピクセルはRGB565のまま書き込まれるため、色情報は失われません。
+    /// BMP形式には透明色がないため、透明色は保存されません。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像サイズが0か`buf`の長さが[`bmp_size`]より短い場合、`Error`を返します。
+    /// [`bmp_size`]が4GiB以上になる大きな画像はBMP形式で表せないため、`Error::SizeMismatch`を返します。
+    /// 
+    /// [`bmp_size`]: Image::bmp_size
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut image = Image::new(3, 2);
+    /// image[(2, 1)] = Pixel::CYAN;
+    /// 
+    /// let mut buf = vec![0u8; image.bmp_size()];
+    /// image.to_bmp(&mut buf)?;
+    /// 
+    /// assert_eq!(Image::from_bmp(&buf)?, image);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bmp(&self, buf: &mut impl AsMut<[u8]>) -> Result<()> {
+        let buf = buf.as_mut();
+
+        if self.width() == 0 || self.height() == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+
+        // BMP形式のファイルサイズは32ビットで表される
+        let size = self.bmp_size();
+        let stride = row_stride(self.width() as usize, 16);
+        let file_size = u32::try_from(size).map_err(|_| Error::SizeMismatch)?;
+        let data_size = u32::try_from(stride * self.height() as usize).map_err(|_| Error::SizeMismatch)?;
+        if buf.len() < size {
+            return Err(Error::OutputBufferTooSmall);
+        }
+
+        // ファイルヘッダー
+        buf[0..2].copy_from_slice(b"BM");
+        buf[2..6].copy_from_slice(&file_size.to_le_bytes());
+        buf[6..10].fill(0);
+        buf[10..14].copy_from_slice(&(PIXEL_OFFSET as u32).to_le_bytes());
+
+        // BITMAPINFOHEADER
+        buf[14..18].copy_from_slice(&(INFO_HEADER_SIZE as u32).to_le_bytes());
+        buf[18..22].copy_from_slice(&(self.width() as i32).to_le_bytes());
+        buf[22..26].copy_from_slice(&(self.height() as i32).to_le_bytes());
+        buf[26..28].copy_from_slice(&1u16.to_le_bytes());
+        buf[28..30].copy_from_slice(&16u16.to_le_bytes());
+        buf[30..34].copy_from_slice(&BI_BITFIELDS.to_le_bytes());
+        buf[34..38].copy_from_slice(&data_size.to_le_bytes());
+        buf[38..42].copy_from_slice(&RESOLUTION.to_le_bytes());
+        buf[42..46].copy_from_slice(&RESOLUTION.to_le_bytes());
+        buf[46..54].fill(0);
+
+        // RGB565のビットマスク
+        buf[54..58].copy_from_slice(&0xF800u32.to_le_bytes());
+        buf[58..62].copy_from_slice(&0x07E0u32.to_le_bytes());
+        buf[62..66].copy_from_slice(&0x001Fu32.to_le_bytes());
+
+        // ピクセルデータは下の行から書き込む
+        let data = &mut buf[PIXEL_OFFSET..size];
+        for (row, line) in self.pixels().chunks_exact(self.width() as usize).rev().zip(data.chunks_exact_mut(stride)) {
+            let (pixels, padding) = line.split_at_mut(self.width() as usize * 2);
+            for (bytes, pixel) in pixels.chunks_exact_mut(2).zip(row) {
+                bytes.copy_from_slice(&pixel.0.to_le_bytes());
+            }
+            padding.fill(0);
+        }
+
+        Ok(())
+    }
+
+    /// `buf`からBMP画像を読み取り、`Image`を作成します。
+    /// 
+    /// 16ビット(BI_RGB、BI_BITFIELDS)、24ビット、32ビットの非圧縮画像に対応しています。
+    /// 色情報はRGB565に減色されます。アルファマスクを持つ画像では完全に透明なピクセルが透明色になります。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正か対応していない形式の場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,no_run
+    /// # use limg::{Image, Result};
+    /// # fn main() -> Result<()> {
+    /// let buf = std::fs::read("image.bmp")?;
+    /// let image = Image::from_bmp(buf)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bmp(buf: impl AsRef<[u8]>) -> Result<Image> {
+        let buf = buf.as_ref();
+
+        if buf.len() < FILE_HEADER_SIZE + INFO_HEADER_SIZE {
+            return Err(Error::InputBufferTooSmall);
+        }
+        if &buf[0..2] != b"BM" {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        let pixel_offset = read_u32(buf, 10) as usize;
+        let info_size = read_u32(buf, 14) as usize;
+        if info_size < INFO_HEADER_SIZE {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        let width = read_u32(buf, 18) as i32;
+        let height = read_u32(buf, 22) as i32;
+        let bits_per_pixel = read_u16(buf, 28) as usize;
+        let compression = read_u32(buf, 30);
+
+        if width == 0 || height == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+        let top_down = height < 0;
+        let width = u16::try_from(width).map_err(|_| Error::UnsupportedFormat)?;
+        let height = u16::try_from(height.unsigned_abs()).map_err(|_| Error::UnsupportedFormat)?;
+
+        // ビットマスクはBITMAPINFOHEADERの直後か、V2以降のヘッダー内にある
+        let masks = |count: usize| -> Result<[u32; 4]> {
+            let end = FILE_HEADER_SIZE + INFO_HEADER_SIZE + count * 4;
+            if buf.len() < end {
+                return Err(Error::InputBufferTooSmall);
+            }
+            let mut masks = [0u32; 4];
+            for (i, mask) in masks.iter_mut().take(count).enumerate() {
+                *mask = read_u32(buf, FILE_HEADER_SIZE + INFO_HEADER_SIZE + i * 4);
+                if !is_contiguous(*mask) {
+                    return Err(Error::UnsupportedFormat);
+                }
+            }
+            Ok(masks)
+        };
+        let mask_count = if info_size >= INFO_HEADER_SIZE + 16 { 4 } else { 3 };
+
+        let layout = match (bits_per_pixel, compression) {
+            (16, BI_RGB) => Layout::Masks { r: 0x7C00, g: 0x03E0, b: 0x001F, a: 0 },
+            (16, BI_BITFIELDS) => match masks(mask_count)? {
+                [0xF800, 0x07E0, 0x001F, 0] => Layout::Rgb565,
+                [r, g, b, a] => Layout::Masks { r, g, b, a },
+            },
+            (24, BI_RGB) => Layout::Bgr24,
+            (32, BI_RGB) => Layout::Masks { r: 0x00FF0000, g: 0x0000FF00, b: 0x000000FF, a: 0 },
+            (32, BI_BITFIELDS) => {
+                let [r, g, b, a] = masks(mask_count)?;
+                Layout::Masks { r, g, b, a }
+            },
+            _ => return Err(Error::UnsupportedFormat),
+        };
+
+        let stride = row_stride(width as usize, bits_per_pixel);
+        let data_size = stride * height as usize;
+        if buf.len() < pixel_offset.saturating_add(data_size) {
+            return Err(Error::InputBufferTooSmall);
+        }
+        let data = &buf[pixel_offset..pixel_offset + data_size];
+
+        let num_pixels = width as usize * height as usize;
+        let mut pixels = Vec::with_capacity(num_pixels);
+        let mut alpha = Vec::new();
+
+        for y in 0..height as usize {
+            let row = if top_down { y } else { height as usize - 1 - y };
+            let line = &data[row * stride..(row + 1) * stride];
+
+            match layout {
+                Layout::Rgb565 => {
+                    pixels.extend(line.chunks_exact(2).take(width as usize).map(|c| Pixel(u16::from_le_bytes([c[0], c[1]]))));
+                },
+                Layout::Bgr24 => {
+                    pixels.extend(line.chunks_exact(3).take(width as usize).map(|c| Pixel::from_rgb([c[2], c[1], c[0]])));
+                },
+                Layout::Masks { r, g, b, a } => {
+                    for x in 0..width as usize {
+                        let value = match bits_per_pixel {
+                            16 => read_u16(line, x * 2) as u32,
+                            _ => read_u32(line, x * 4),
+                        };
+                        pixels.push(Pixel::from_rgb([channel(value, r), channel(value, g), channel(value, b)]));
+                        if a != 0 {
+                            alpha.push(channel(value, a));
+                        }
+                    }
+                },
+            }
+        }
+
+        // アルファ値がすべて0の画像は不透明として扱う
+        let transparent_color = if alpha.iter().any(|&a| a != 0) {
+            key_transparent_pixels(&mut pixels, |i| alpha[i] == 0)?
+        } else {
+            None
+        };
+
+        Ok(Image::from_parts(width, height, transparent_color, pixels.into_boxed_slice()))
+    }
+}
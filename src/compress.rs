@@ -0,0 +1,402 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use limg_core::{ImageSpec, PixelEndian, PIXEL_BYTES};
+
+use crate::pixel::Pixel;
+use crate::header::{self, FLAG_LITTLE_ENDIAN, FLAG_TRANSPARENT};
+use crate::decoder::{ByteSource, CountingSource, DecodeOptions};
+use crate::error::{Error, HeaderField, Result};
+
+/// 圧縮されたLimg画像のシグネチャ
+/// 
+/// 圧縮に対応していないデコーダーでは`Error::UnsupportedFormat`になります。
+pub(crate) const SIGNATURE: [u8; 4] = *b"LIMZ";
+
+/// 対応している圧縮形式のバージョン
+const VERSION: u8 = 1;
+
+/// 圧縮方式を示すフラグのシフト量
+const COMPRESSION_SHIFT: u8 = 2;
+
+/// 圧縮方式を示すフラグのマスク
+const COMPRESSION_MASK: u8 = 0b1100;
+
+/// 圧縮されたLimg画像のヘッダーのサイズ
+/// 
+/// Limg画像のヘッダーと同じ並びの12バイトの後に、圧縮データのバイト数(4)が続きます。
+pub(crate) const COMPRESSED_HEADER_SIZE: usize = 16;
+
+/// ランレングスのパケットの最大ピクセル数
+const MAX_PACKET: usize = 128;
+
+/// ランレングスのパケットが連続する同じピクセルであることを示すビット
+const RUN_BIT: u8 = 0x80;
+
+/// LZ方式で一致とみなす最小のピクセル数
+const MIN_MATCH: usize = 2;
+
+/// LZ方式で参照できる最大の距離
+const MAX_OFFSET: usize = u16::MAX as usize;
+
+/// LZ方式の一致を探すハッシュテーブルのビット数
+const HASH_BITS: u32 = 12;
+
+/// ピクセルデータの圧縮方式です。
+/// 
+/// 単色の多いUI素材などで、ファイルサイズを大きく削減できます。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// ピクセル単位のランレングス符号化
+    /// 
+    /// アロケーションなしでストリーミングしながらデコードできます。
+    Rle16,
+
+    /// ピクセル単位のLZ4に似た辞書式圧縮
+    /// 
+    /// 繰り返し模様にも効果がありますが、[`LimgDecoder`]では読み込み時に画像全体を展開します。
+    /// バッファからのデコードではアロケーションは行いません。
+    /// 
+    /// [`LimgDecoder`]: crate::LimgDecoder
+    Lz4Like,
+}
+
+impl Compression {
+    #[inline(always)]
+    fn to_bits(self) -> u8 {
+        match self {
+            Compression::Rle16 => 1,
+            Compression::Lz4Like => 2,
+        }
+    }
+
+    #[inline(always)]
+    fn from_bits(bits: u8) -> Option<Compression> {
+        match bits {
+            1 => Some(Compression::Rle16),
+            2 => Some(Compression::Lz4Like),
+            _ => None,
+        }
+    }
+}
+
+/// 圧縮されたLimg画像のヘッダー情報
+pub(crate) struct CompressedHeader {
+    pub(crate) spec: ImageSpec,
+    pub(crate) compression: Compression,
+
+    /// ヘッダーに続く圧縮データのバイト数
+    pub(crate) payload_len: usize,
+}
+
+/// `buf`が圧縮されたLimg画像のシグネチャで始まる場合`true`を返します。
+#[inline(always)]
+pub(crate) fn is_compressed(buf: &[u8]) -> bool {
+    buf.starts_with(&SIGNATURE)
+}
+
+/// 圧縮されたLimg画像のヘッダーを読み取ります。
+pub(crate) fn decode_header(buf: &[u8]) -> Result<CompressedHeader> {
+    if buf.len() < COMPRESSED_HEADER_SIZE {
+        return Err(Error::Truncated { expected: COMPRESSED_HEADER_SIZE, actual: buf.len() });
+    }
+    let spec = header::parse_common(buf, &SIGNATURE, VERSION, FLAG_LITTLE_ENDIAN | FLAG_TRANSPARENT | COMPRESSION_MASK)
+        .map_err(|err| err.into_error(buf.len()))?;
+
+    let flags = buf[header::FLAGS_OFFSET];
+    let compression = Compression::from_bits((flags & COMPRESSION_MASK) >> COMPRESSION_SHIFT)
+        .ok_or(Error::InvalidHeader { field: HeaderField::Flags, offset: header::FLAGS_OFFSET })?;
+    let payload_len = u32::from_le_bytes([buf[12], buf[13], buf[14], buf[15]]) as usize;
+
+    Ok(CompressedHeader { spec, compression, payload_len })
+}
+
+/// `pixels`を圧縮し、ヘッダーを含むバイト列を返します。
+pub(crate) fn encode(pixels: &[Pixel], spec: &ImageSpec, compression: Compression) -> Result<Vec<u8>> {
+    if spec.width == 0 || spec.height == 0 {
+        return Err(Error::ZeroImageDimensions);
+    }
+
+    let mut buf = vec![0u8; COMPRESSED_HEADER_SIZE];
+    match compression {
+        Compression::Rle16 => encode_rle(pixels, spec.pixel_endian, &mut buf),
+        Compression::Lz4Like => encode_lz(pixels, spec.pixel_endian, &mut buf),
+    }
+    let payload_len = u32::try_from(buf.len() - COMPRESSED_HEADER_SIZE).map_err(|_| Error::SizeMismatch)?;
+
+    let mut flags = compression.to_bits() << COMPRESSION_SHIFT;
+    if matches!(spec.pixel_endian, PixelEndian::Little) {
+        flags |= FLAG_LITTLE_ENDIAN;
+    }
+    if spec.transparent_color.is_some() {
+        flags |= FLAG_TRANSPARENT;
+    }
+
+    buf[0..4].copy_from_slice(&SIGNATURE);
+    buf[4] = VERSION;
+    buf[5] = flags;
+    buf[6..8].copy_from_slice(&spec.width.to_le_bytes());
+    buf[8..10].copy_from_slice(&spec.height.to_le_bytes());
+    buf[10..12].copy_from_slice(&spec.transparent_color.unwrap_or(0).to_le_bytes());
+    buf[12..16].copy_from_slice(&payload_len.to_le_bytes());
+
+    Ok(buf)
+}
+
+/// 圧縮されたLimg画像の`buf`から`pixels`にピクセルをデコードします。
+/// 
+/// アロケーションは行いません。
+/// `options.strict`が`false`で圧縮データが不足している場合、展開できたピクセル以外は`Pixel::BLACK`になります。
+pub(crate) fn decode_into(buf: &[u8], pixels: &mut [Pixel], options: &DecodeOptions) -> Result<CompressedHeader> {
+    let header = decode_header(buf)?;
+    let end = COMPRESSED_HEADER_SIZE + header.payload_len;
+    let truncated = options.check_length(end, buf.len())?;
+    let payload = &buf[COMPRESSED_HEADER_SIZE..end.min(buf.len())];
+    let pixels = pixels.get_mut(..header.spec.num_pixels()).ok_or(Error::OutputBufferTooSmall)?;
+
+    if truncated {
+        // 途中まで展開できたピクセルを残す
+        pixels.fill(Pixel::BLACK);
+        let _ = decode_payload(payload, header.compression, header.spec.pixel_endian, pixels);
+    } else {
+        decode_payload(payload, header.compression, header.spec.pixel_endian, pixels)?;
+    }
+    Ok(header)
+}
+
+/// 圧縮データ`payload`を`pixels`に展開します。
+/// 
+/// 圧縮データが途中で終わっている場合は、ヘッダーを含めたバイト数で`Error::Truncated`を返します。
+pub(crate) fn decode_payload(payload: &[u8], compression: Compression, endian: PixelEndian, pixels: &mut [Pixel]) -> Result<()> {
+    match compression {
+        Compression::Rle16 => {
+            let mut source = CountingSource::new(payload, COMPRESSED_HEADER_SIZE, COMPRESSED_HEADER_SIZE + payload.len());
+            RleReader::new().read(&mut source, endian, pixels)
+        },
+        Compression::Lz4Like => decode_lz(payload, endian, pixels),
+    }
+}
+
+/// ランレングス符号化でピクセルを圧縮します。
+/// 
+/// 制御バイトの最上位ビットが立っている場合は`(下位7ビット + 1)`個の同じピクセルが、
+/// それ以外の場合は`(制御バイト + 1)`個のピクセルがそのまま続きます。
+fn encode_rle(pixels: &[Pixel], endian: PixelEndian, buf: &mut Vec<u8>) {
+    let mut i = 0;
+    let mut literal_start = 0;
+
+    let flush_literal = |buf: &mut Vec<u8>, literal: &[Pixel]| {
+        for chunk in literal.chunks(MAX_PACKET) {
+            buf.push((chunk.len() - 1) as u8);
+            chunk.iter().for_each(|pixel| buf.extend_from_slice(&pixel.to_bytes(endian)));
+        }
+    };
+
+    while i < pixels.len() {
+        let run = pixels[i..].iter().take(MAX_PACKET).take_while(|&&p| p == pixels[i]).count();
+        if run >= 2 {
+            flush_literal(buf, &pixels[literal_start..i]);
+            buf.push(RUN_BIT | (run - 1) as u8);
+            buf.extend_from_slice(&pixels[i].to_bytes(endian));
+            i += run;
+            literal_start = i;
+        } else {
+            i += 1;
+        }
+    }
+    flush_literal(buf, &pixels[literal_start..]);
+}
+
+/// ランレングス符号化されたピクセルを読み進めながら展開します。
+/// 
+/// パケットの途中で止めることができるため、行単位のデコードに使用できます。
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RleReader {
+    /// 現在のパケットの残りピクセル数
+    remaining: usize,
+
+    /// 現在のパケットが同じピクセルの連続の場合はそのピクセル
+    run: Option<Pixel>,
+}
+
+impl RleReader {
+    #[inline(always)]
+    pub(crate) fn new() -> RleReader {
+        RleReader::default()
+    }
+
+    /// `source`から`pixels`を満たすまで展開します。
+    pub(crate) fn read<S: ByteSource + ?Sized>(&mut self, source: &mut S, endian: PixelEndian, pixels: &mut [Pixel]) -> Result<()> {
+        let mut pos = 0;
+        while pos < pixels.len() {
+            if self.remaining == 0 {
+                let mut control = [0u8];
+                source.read_exact(&mut control)?;
+                self.remaining = (control[0] & !RUN_BIT) as usize + 1;
+                self.run = if control[0] & RUN_BIT != 0 {
+                    let mut bytes = [0u8; PIXEL_BYTES];
+                    source.read_exact(&mut bytes)?;
+                    Some(Pixel::from_bytes(bytes, endian))
+                } else {
+                    None
+                };
+            }
+
+            let len = self.remaining.min(pixels.len() - pos);
+            let dst = &mut pixels[pos..pos + len];
+            match self.run {
+                Some(pixel) => dst.fill(pixel),
+                None => {
+                    for pixel in dst {
+                        let mut bytes = [0u8; PIXEL_BYTES];
+                        source.read_exact(&mut bytes)?;
+                        *pixel = Pixel::from_bytes(bytes, endian);
+                    }
+                },
+            }
+
+            self.remaining -= len;
+            pos += len;
+        }
+
+        Ok(())
+    }
+}
+
+/// LZ方式の長さを書き込みます。
+/// 
+/// `nibble`に収まらない分は`255`を続けて書き込みます。
+fn push_length(buf: &mut Vec<u8>, mut len: usize) {
+    while len >= 255 {
+        buf.push(255);
+        len -= 255;
+    }
+    buf.push(len as u8);
+}
+
+/// LZ方式の1シーケンスを書き込みます。
+fn push_sequence(buf: &mut Vec<u8>, literal: &[Pixel], matched: Option<(usize, usize)>, endian: PixelEndian) {
+    let lit_nibble = literal.len().min(15);
+    let match_nibble = matched.map_or(0, |(_, len)| (len - MIN_MATCH).min(15));
+    buf.push(((lit_nibble as u8) << 4) | match_nibble as u8);
+
+    if lit_nibble == 15 {
+        push_length(buf, literal.len() - 15);
+    }
+    literal.iter().for_each(|pixel| buf.extend_from_slice(&pixel.to_bytes(endian)));
+
+    if let Some((offset, len)) = matched {
+        buf.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_nibble == 15 {
+            push_length(buf, len - MIN_MATCH - 15);
+        }
+    }
+}
+
+/// LZ4に似た形式でピクセルを圧縮します。
+/// 
+/// 各シーケンスはトークン(上位4ビットがリテラル数、下位4ビットが一致長 - 2)、リテラルのピクセル、
+/// 一致した位置までの距離(2バイト)の順に並びます。最後のシーケンスはリテラルのみです。
+fn encode_lz(pixels: &[Pixel], endian: PixelEndian, buf: &mut Vec<u8>) {
+    let hash = |i: usize| {
+        let key = (pixels[i].0 as u32) << 16 | pixels[i + 1].0 as u32;
+        (key.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+    };
+
+    let mut table = vec![usize::MAX; 1 << HASH_BITS];
+    let mut literal_start = 0;
+    let mut i = 0;
+
+    while i + MIN_MATCH <= pixels.len() {
+        let h = hash(i);
+        let candidate = table[h];
+        table[h] = i;
+
+        if candidate != usize::MAX && i - candidate <= MAX_OFFSET && pixels[candidate..candidate + MIN_MATCH] == pixels[i..i + MIN_MATCH] {
+            let len = pixels[i..].iter().zip(&pixels[candidate..]).take_while(|(a, b)| a == b).count();
+            push_sequence(buf, &pixels[literal_start..i], Some((i - candidate, len)), endian);
+
+            // 一致した範囲もハッシュテーブルに登録する
+            let end = i + len;
+            for j in (i + 1..end).filter(|&j| j + MIN_MATCH <= pixels.len()) {
+                table[hash(j)] = j;
+            }
+            i = end;
+            literal_start = i;
+        } else {
+            i += 1;
+        }
+    }
+
+    push_sequence(buf, &pixels[literal_start..], None, endian);
+}
+
+/// LZ方式の圧縮データ`payload`が`end`バイト目より前で終わっていることを表す`Error`を返します。
+#[inline(always)]
+fn lz_truncated(payload: &[u8], end: usize) -> Error {
+    Error::Truncated { expected: COMPRESSED_HEADER_SIZE + end, actual: COMPRESSED_HEADER_SIZE + payload.len() }
+}
+
+/// LZ方式の長さの続きを読み取ります。
+fn read_length(payload: &[u8], pos: &mut usize) -> Result<usize> {
+    let mut len = 0;
+    loop {
+        let byte = *payload.get(*pos).ok_or_else(|| lz_truncated(payload, *pos + 1))?;
+        *pos += 1;
+        len += byte as usize;
+        if byte != 255 {
+            return Ok(len);
+        }
+    }
+}
+
+/// LZ方式で圧縮されたピクセルを`pixels`に展開します。
+/// 
+/// 展開済みのピクセルを辞書として参照するため、アロケーションは行いません。
+fn decode_lz(payload: &[u8], endian: PixelEndian, pixels: &mut [Pixel]) -> Result<()> {
+    let mut pos = 0;
+    let mut out = 0;
+
+    loop {
+        let token = *payload.get(pos).ok_or_else(|| lz_truncated(payload, pos + 1))?;
+        pos += 1;
+
+        let mut literal_len = (token >> 4) as usize;
+        if literal_len == 15 {
+            literal_len += read_length(payload, &mut pos)?;
+        }
+        let literal = payload.get(pos..pos + literal_len * PIXEL_BYTES).ok_or_else(|| lz_truncated(payload, pos + literal_len * PIXEL_BYTES))?;
+        let dst = pixels.get_mut(out..out + literal_len).ok_or(Error::UnsupportedFormat)?;
+        for (pixel, bytes) in dst.iter_mut().zip(literal.chunks_exact(PIXEL_BYTES)) {
+            *pixel = Pixel::from_bytes([bytes[0], bytes[1]], endian);
+        }
+        pos += literal.len();
+        out += literal_len;
+
+        if out == pixels.len() {
+            return Ok(());
+        }
+
+        let offset = payload.get(pos..pos + 2).ok_or_else(|| lz_truncated(payload, pos + 2))?;
+        let offset = u16::from_le_bytes([offset[0], offset[1]]) as usize;
+        pos += 2;
+
+        let mut match_len = (token & 0x0F) as usize + MIN_MATCH;
+        if match_len == 15 + MIN_MATCH {
+            match_len += read_length(payload, &mut pos)?;
+        }
+        if offset == 0 || offset > out || out + match_len > pixels.len() {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        // 範囲が重なる場合があるため1ピクセルずつコピーする
+        for i in out..out + match_len {
+            pixels[i] = pixels[i - offset];
+        }
+        out += match_len;
+
+        if out == pixels.len() {
+            return Ok(());
+        }
+    }
+}
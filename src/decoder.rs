@@ -0,0 +1,413 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::image::Image;
+use crate::pixel::Pixel;
+use crate::header;
+use crate::compress::{self, Compression, RleReader, COMPRESSED_HEADER_SIZE};
+use crate::error::{Error, Result};
+use core::slice::from_raw_parts_mut;
+use limg_core::{ImageSpec, HEADER_SIZE, PIXEL_BYTES};
+
+/// [`LimgDecoder`]にバイト列を供給するソースです。
+/// 
+/// `std`機能が有効な場合、[`std::io::Read`]を実装するすべての型で実装されます。
+/// 無効な場合は`&[u8]`で実装されます。
+/// 
+/// [`std::io::Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
+pub trait ByteSource {
+    /// `buf`を満たすバイト列を読み込みます。
+    /// 
+    /// # Errors
+    /// 
+    /// `buf`を満たす前にデータが終わった場合、`expected`を`buf`の長さ、`actual`を読み込めたバイト数とした`Error::Truncated`を返します。
+    /// 読み込みに失敗した場合もそれ以外の`Error`を返します。
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()>;
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> ByteSource for R {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        // `std::io::Read::read_exact`では読み込めたバイト数が分からないため、1回ずつ読み込む
+        let mut filled = 0;
+        while filled < buf.len() {
+            match std::io::Read::read(self, &mut buf[filled..]) {
+                Ok(0) => return Err(Error::Truncated { expected: buf.len(), actual: filled }),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {},
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ByteSource for &[u8] {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        if self.len() < buf.len() {
+            return Err(Error::Truncated { expected: buf.len(), actual: self.len() });
+        }
+
+        let (head, tail) = self.split_at(buf.len());
+        buf.copy_from_slice(head);
+        *self = tail;
+
+        Ok(())
+    }
+}
+
+/// 読み込んだバイト数を数える[`ByteSource`]です。
+/// 
+/// データが途中で終わった場合の`Error::Truncated`を、データの先頭からのバイト数に変換します。
+pub(crate) struct CountingSource<S> {
+    /// 読み込み元
+    inner: S,
+
+    /// 読み込んだバイト数
+    offset: usize,
+
+    /// 必要なバイト数
+    expected: usize,
+}
+
+impl<S: ByteSource> CountingSource<S> {
+    /// `offset`バイト読み込み済みの`inner`から、全体で`expected`バイト読み込むソースを作成します。
+    #[inline(always)]
+    pub(crate) fn new(inner: S, offset: usize, expected: usize) -> CountingSource<S> {
+        CountingSource { inner, offset, expected }
+    }
+
+    /// 必要なバイト数を設定します。
+    #[inline(always)]
+    pub(crate) fn set_expected(&mut self, expected: usize) {
+        self.expected = expected;
+    }
+
+    /// 読み込み元を返します。
+    #[inline(always)]
+    pub(crate) fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: ByteSource> ByteSource for CountingSource<S> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        match self.inner.read_exact(buf) {
+            Ok(()) => {
+                self.offset += buf.len();
+                Ok(())
+            },
+            Err(Error::Truncated { actual, .. }) => Err(Error::Truncated {
+                // 壊れた圧縮データはヘッダーの長さより多く読み込もうとする場合がある
+                expected: self.expected.max(self.offset + buf.len()),
+                actual: self.offset + actual,
+            }),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// 圧縮データを読み込むときに一度に確保するバイト数
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// ピクセルデータの読み込み方法
+enum Payload {
+    /// 圧縮されていないピクセル
+    Raw,
+
+    /// ランレングス符号化されたピクセル
+    Rle(RleReader),
+
+    /// 読み込み時に展開したピクセル
+    Expanded(Box<[Pixel]>),
+}
+
+/// 画像をデコードするときの検証方法です。
+/// 
+/// [`Image::from_buffer_with_options`]や[`Image::from_read_with_options`]に指定します。
+/// デフォルトでは、ピクセルデータが不足している画像はエラーになり、画像データの後の余分なデータは無視されます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{DecodeOptions, Error, Image, Pixel, Result};
+/// # fn main() -> Result<()> {
+/// # let mut buf = vec![0u8; 12 + 4 * 4 * 2];
+/// # Image::new(4, 4).to_buffer(&mut buf)?;
+/// // 末尾が欠けた画像
+/// let truncated = &buf[..buf.len() - 8];
+/// assert!(matches!(Image::from_buffer(truncated), Err(Error::Truncated { expected: 44, actual: 36 })));
+/// 
+/// // 不足したピクセルは黒で埋められる
+/// let image = Image::from_buffer_with_options(truncated, DecodeOptions::LENIENT)?;
+/// assert_eq!(image[(3, 3)], Pixel::BLACK);
+/// 
+/// // 余分なデータを拒否する
+/// buf.push(0);
+/// assert!(matches!(Image::from_buffer_with_options(&buf, DecodeOptions::STRICT), Err(Error::TrailingData { .. })));
+/// # Ok(())
+/// # }
+/// ```
+/// 
+/// [`Image::from_buffer_with_options`]: crate::Image::from_buffer_with_options
+/// [`Image::from_read_with_options`]: crate::Image::from_read_with_options
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DecodeOptions {
+    /// ピクセルデータが不足している画像をエラーにするかどうか
+    /// 
+    /// `true`の場合は`Error::Truncated`を返します。
+    /// `false`の場合は読み込めたピクセルを残し、不足したピクセルを`Pixel::BLACK`で埋めます。
+    pub strict: bool,
+
+    /// 画像データの後の余分なデータを許可するかどうか
+    /// 
+    /// `false`の場合は`Error::TrailingData`を返します。
+    pub allow_trailing_data: bool,
+}
+
+impl DecodeOptions {
+    /// 不足したデータも余分なデータもエラーにする設定です。
+    pub const STRICT: DecodeOptions = DecodeOptions { strict: true, allow_trailing_data: false };
+
+    /// 不足したピクセルを黒で埋め、余分なデータを無視する設定です。
+    pub const LENIENT: DecodeOptions = DecodeOptions { strict: false, allow_trailing_data: true };
+
+    /// データの長さ`actual`を画像のバイト数`expected`と比較します。
+    /// 
+    /// データが不足していて読み込みを続ける場合`true`を返します。
+    pub(crate) fn check_length(&self, expected: usize, actual: usize) -> Result<bool> {
+        if actual < expected {
+            if self.strict {
+                return Err(Error::Truncated { expected, actual });
+            }
+            return Ok(true);
+        }
+        if actual > expected && !self.allow_trailing_data {
+            return Err(Error::TrailingData { expected, actual });
+        }
+
+        Ok(false)
+    }
+}
+
+impl Default for DecodeOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        DecodeOptions { strict: true, allow_trailing_data: true }
+    }
+}
+
+/// Limg画像を行単位でデコードするデコーダーです。
+/// 
+/// 画像全体をメモリに展開せず、呼び出し側のバッファに1行ずつ、または複数行ずつピクセルを読み込みます。
+/// 圧縮された画像も読み込めます。[`Compression::Lz4Like`]の場合は作成時に画像全体を展開します。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, LimgDecoder, Pixel, Result};
+/// # fn main() -> Result<()> {
+/// # let mut buf = vec![0u8; 1024];
+/// # Image::new(10, 10).to_buffer(&mut buf)?;
+/// let mut decoder = LimgDecoder::new(buf.as_slice())?;
+/// let mut row = vec![Pixel::WHITE; decoder.width() as usize];
+/// 
+/// while decoder.read_row(&mut row)? != 0 {
+///     assert!(row.iter().all(|&p| p == Pixel::BLACK));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct LimgDecoder<S: ByteSource> {
+    /// 読み込み元
+    source: CountingSource<S>,
+
+    /// ヘッダー情報
+    spec: ImageSpec,
+
+    /// 次に読み込む行
+    row: u16,
+
+    /// ピクセルデータの読み込み方法
+    payload: Payload,
+
+    /// 圧縮方式
+    compression: Option<Compression>,
+}
+
+impl<S: ByteSource> LimgDecoder<S> {
+    /// `source`からヘッダーを読み込み、デコーダーを作成します。
+    /// 
+    /// # Errors
+    /// 
+    /// ヘッダーが不正か読み込みに失敗した場合、`Error`を返します。
+    /// データが途中で終わっている場合は、以降の読み込みも含めて`Error::Truncated`を返します。
+    pub fn new(source: S) -> Result<LimgDecoder<S>> {
+        let mut source = CountingSource::new(source, 0, HEADER_SIZE);
+
+        let mut header_buf = [0u8; COMPRESSED_HEADER_SIZE];
+        source.read_exact(&mut header_buf[..HEADER_SIZE])?;
+        if !compress::is_compressed(&header_buf) {
+            let spec = header::check_header(&header_buf[..HEADER_SIZE])?;
+            source.set_expected(HEADER_SIZE + spec.num_pixels() * PIXEL_BYTES);
+            return Ok(LimgDecoder { source, spec, row: 0, payload: Payload::Raw, compression: None });
+        }
+
+        // 圧縮されている場合は残りのヘッダーを読み込む
+        source.set_expected(COMPRESSED_HEADER_SIZE);
+        source.read_exact(&mut header_buf[HEADER_SIZE..])?;
+        let header = compress::decode_header(&header_buf)?;
+        source.set_expected(COMPRESSED_HEADER_SIZE + header.payload_len);
+        let payload = match header.compression {
+            Compression::Rle16 => Payload::Rle(RleReader::new()),
+            Compression::Lz4Like => {
+                // ヘッダーの長さを信用せず、読み込めた分だけバッファを広げる
+                let mut data = Vec::new();
+                while data.len() < header.payload_len {
+                    let start = data.len();
+                    data.resize(start + (header.payload_len - start).min(READ_CHUNK_SIZE), 0);
+                    source.read_exact(&mut data[start..])?;
+                }
+                let mut pixels = vec![Pixel::BLACK; header.spec.num_pixels()].into_boxed_slice();
+                compress::decode_payload(&data, header.compression, header.spec.pixel_endian, &mut pixels)?;
+                Payload::Expanded(pixels)
+            },
+        };
+
+        Ok(LimgDecoder { source, spec: header.spec, row: 0, payload, compression: Some(header.compression) })
+    }
+
+    /// デコードしたヘッダー情報を返します。
+    #[inline(always)]
+    pub fn spec(&self) -> &ImageSpec {
+        &self.spec
+    }
+
+    /// 画像の幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.spec.width
+    }
+
+    /// 画像の高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.spec.height
+    }
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    #[inline(always)]
+    pub fn transparent_color(&self) -> Option<Pixel> {
+        self.spec.transparent_color.map(Pixel)
+    }
+
+    /// ピクセルデータの圧縮方式を返します。
+    /// 
+    /// 圧縮されていない場合`None`になります。
+    #[inline(always)]
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    /// まだ読み込んでいない行数を返します。
+    #[inline(always)]
+    pub fn remaining_rows(&self) -> u16 {
+        self.spec.height - self.row
+    }
+
+    /// 次の1行を`buf`に読み込みます。
+    /// 
+    /// 読み込んだ行数を返します。すべての行を読み込み済みの場合は`0`を返します。
+    /// 
+    /// # Errors
+    /// 
+    /// `buf`の長さが画像の幅より短いか読み込みに失敗した場合、`Error`を返します。
+    pub fn read_row(&mut self, buf: &mut [Pixel]) -> Result<usize> {
+        let width = self.spec.width as usize;
+        if buf.len() < width {
+            return Err(Error::OutputBufferTooSmall);
+        }
+
+        self.read_rows(&mut buf[..width])
+    }
+
+    /// `buf`に入るだけの行を読み込みます。
+    /// 
+    /// 読み込んだ行数を返します。すべての行を読み込み済みの場合は`0`を返します。
+    /// 
+    /// # Errors
+    /// 
+    /// `buf`の長さが画像の幅より短いか読み込みに失敗した場合、`Error`を返します。
+    pub fn read_rows(&mut self, buf: &mut [Pixel]) -> Result<usize> {
+        let width = self.spec.width as usize;
+        let rows = buf.len().checked_div(width).unwrap_or(0).min(self.remaining_rows() as usize);
+
+        if rows == 0 {
+            return if self.remaining_rows() == 0 { Ok(0) } else { Err(Error::OutputBufferTooSmall) };
+        }
+
+        let pixels = &mut buf[..rows * width];
+        match &mut self.payload {
+            Payload::Raw => {
+                let bytes = unsafe { from_raw_parts_mut(pixels.as_mut_ptr().cast::<u8>(), pixels.len() * PIXEL_BYTES) };
+                self.source.read_exact(bytes)?;
+
+                // バイト列のままのピクセルをエンディアン変換
+                for pixel in pixels {
+                    *pixel = Pixel::from_bytes(pixel.0.to_ne_bytes(), self.spec.pixel_endian);
+                }
+            },
+            Payload::Rle(reader) => reader.read(&mut self.source, self.spec.pixel_endian, pixels)?,
+            Payload::Expanded(expanded) => {
+                let start = self.row as usize * width;
+                pixels.copy_from_slice(&expanded[start..start + pixels.len()]);
+            },
+        }
+
+        self.row += rows as u16;
+        Ok(rows)
+    }
+
+    /// 残りの行をすべて読み込み、`Image`を作成します。
+    /// 
+    /// 既に読み込んだ行は含まれません。
+    /// 
+    /// # Errors
+    /// 
+    /// 読み込みに失敗した場合、`Error`を返します。
+    pub fn read_image(self) -> Result<Image> {
+        let mut decoder = self;
+        let width = decoder.spec.width;
+        let height = decoder.remaining_rows();
+        let num_pixels = width as usize * height as usize;
+
+        if !matches!(decoder.payload, Payload::Raw) {
+            let mut pixels = vec![Pixel::BLACK; num_pixels].into_boxed_slice();
+            decoder.read_rows(&mut pixels)?;
+            return Ok(Image::from_parts(width, height, decoder.transparent_color(), pixels));
+        }
+
+        // バイナリピクセルデータを直接読み込み
+        let mut pixels = Box::<[Pixel]>::new_uninit_slice(num_pixels);
+        let bytes = unsafe { from_raw_parts_mut(pixels.as_mut_ptr().cast::<u8>(), num_pixels * PIXEL_BYTES) };
+        decoder.source.read_exact(bytes)?;
+        let mut pixels = unsafe { pixels.assume_init() };
+
+        for pixel in pixels.iter_mut() {
+            *pixel = Pixel::from_bytes(pixel.0.to_ne_bytes(), decoder.spec.pixel_endian);
+        }
+
+        Ok(Image::from_parts(width, height, decoder.transparent_color(), pixels))
+    }
+
+    /// デコーダーを破棄し、読み込み元を返します。
+    #[inline(always)]
+    pub fn into_inner(self) -> S {
+        self.source.into_inner()
+    }
+}
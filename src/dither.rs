@@ -0,0 +1,262 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::image::Image;
+use crate::pixel::{Pixel, key_transparent_pixels};
+use crate::error::{Error, Result};
+
+/// RGB888からRGB565へ減色する際のディザリング方法です。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// ディザリングなし
+    /// 
+    /// [`Pixel::from_rgb`]と同じく下位ビットを切り捨てます。
+    #[default]
+    None,
+
+    /// Floyd–Steinbergの誤差拡散
+    FloydSteinberg,
+
+    /// Atkinsonの誤差拡散
+    /// 
+    /// 誤差の3/4のみを拡散するため、コントラストが強く残ります。
+    Atkinson,
+
+    /// 4x4のBayer行列による組織的ディザ
+    Bayer4x4,
+
+    /// 8x8のBayer行列による組織的ディザ
+    Bayer8x8,
+}
+
+/// 誤差拡散の係数`(dx, dy, weight)`
+type Kernel = &'static [(isize, usize, i32)];
+
+const FLOYD_STEINBERG: (Kernel, i32) = (&[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)], 16);
+const ATKINSON: (Kernel, i32) = (&[(1, 0, 1), (2, 0, 1), (-1, 1, 1), (0, 1, 1), (1, 1, 1), (0, 2, 1)], 8);
+
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [ 0,  8,  2, 10],
+    [12,  4, 14,  6],
+    [ 3, 11,  1,  9],
+    [15,  7, 13,  5],
+];
+
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [ 0, 32,  8, 40,  2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44,  4, 36, 14, 46,  6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [ 3, 35, 11, 43,  1, 33,  9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47,  7, 39, 13, 45,  5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// 各チャンネルを最も近いRGB565の値に量子化し、ピクセルと復元した色を返します。
+#[inline(always)]
+fn quantize_nearest(rgb: [i32; 3]) -> (Pixel, [i32; 3]) {
+    let r = ((rgb[0] * 31 + 127) / 255) as u16;
+    let g = ((rgb[1] * 63 + 127) / 255) as u16;
+    let b = ((rgb[2] * 31 + 127) / 255) as u16;
+
+    let pixel = Pixel((r << 11) | (g << 5) | b);
+    (pixel, [pixel.r() as i32, pixel.g() as i32, pixel.b() as i32])
+}
+
+/// `channels`バイトごとに並んだ色データを減色します。
+/// 
+/// `transparent`が`true`を返すピクセルはディザリングせず、誤差の拡散からも除外します。
+fn dither(width: u16, height: u16, data: &[u8], channels: usize, dither: Dither, transparent: impl Fn(usize) -> bool) -> Vec<Pixel> {
+    let (width, height) = (width as usize, height as usize);
+    let rgb = |i: usize| [data[i * channels], data[i * channels + 1], data[i * channels + 2]];
+
+    let mut pixels = Vec::with_capacity(width * height);
+
+    match dither {
+        Dither::None => {
+            pixels.extend((0..width * height).map(|i| Pixel::from_rgb(rgb(i))));
+        },
+        Dither::Bayer4x4 | Dither::Bayer8x8 => {
+            for y in 0..height {
+                for x in 0..width {
+                    let i = y * width + x;
+                    if transparent(i) {
+                        pixels.push(Pixel::from_rgb(rgb(i)));
+                        continue;
+                    }
+
+                    // 量子化幅の範囲で閾値をずらしてから最も近い値に量子化する
+                    let (threshold, levels) = match dither {
+                        Dither::Bayer4x4 => (BAYER_4X4[y % 4][x % 4] as i32, 16),
+                        _ => (BAYER_8X8[y % 8][x % 8] as i32, 64),
+                    };
+                    let offset = |step: i32| (2 * threshold + 1) * step / (2 * levels) - step / 2;
+                    let [r, g, b] = rgb(i);
+                    let target = [r as i32 + offset(8), g as i32 + offset(4), b as i32 + offset(8)];
+                    pixels.push(quantize_nearest(target.map(|v| v.clamp(0, 255))).0);
+                }
+            }
+        },
+        Dither::FloydSteinberg | Dither::Atkinson => {
+            let (kernel, divisor) = match dither {
+                Dither::FloydSteinberg => FLOYD_STEINBERG,
+                _ => ATKINSON,
+            };
+
+            // 現在の行と下2行分の誤差(係数を掛けた値)
+            let mut errors = [vec![[0i32; 3]; width], vec![[0i32; 3]; width], vec![[0i32; 3]; width]];
+
+            for y in 0..height {
+                for x in 0..width {
+                    let i = y * width + x;
+                    if transparent(i) {
+                        pixels.push(Pixel::from_rgb(rgb(i)));
+                        continue;
+                    }
+
+                    let c = rgb(i);
+                    let e = errors[0][x];
+                    let target = [0, 1, 2].map(|ch| (c[ch] as i32 + e[ch] / divisor).clamp(0, 255));
+                    let (pixel, recon) = quantize_nearest(target);
+                    pixels.push(pixel);
+
+                    let err = [0, 1, 2].map(|ch| target[ch] - recon[ch]);
+                    for &(dx, dy, weight) in kernel {
+                        let (Some(tx), ty) = (x.checked_add_signed(dx), y + dy) else {
+                            continue;
+                        };
+                        if tx >= width || ty >= height || transparent(ty * width + tx) {
+                            continue;
+                        }
+                        for ch in 0..3 {
+                            errors[dy][tx][ch] += err[ch] * weight;
+                        }
+                    }
+                }
+
+                errors.rotate_left(1);
+                errors[2].fill([0; 3]);
+            }
+        },
+    }
+
+    pixels
+}
+
+/// RGB888のデータを減色します。
+/// 
+/// [`Image::from_rgb888_dithered`]と[`Image::from_rgb888_dithered_with_transparent_color`]の実装です。
+fn rgb888_dithered(width: u16, height: u16, data: &[u8], transparent_color: Option<[u8; 3]>, dither: Dither) -> Result<Image> {
+    let num_pixels = width as usize * height as usize;
+    if data.len() < num_pixels * 3 {
+        return Err(Error::InputBufferTooSmall);
+    }
+
+    let Some(key) = transparent_color else {
+        let pixels = self::dither(width, height, data, 3, dither, |_| false);
+        return Ok(Image::from_parts(width, height, None, pixels.into_boxed_slice()));
+    };
+
+    let is_key = |i: usize| data[i * 3..i * 3 + 3] == key;
+    let mut pixels = self::dither(width, height, data, 3, dither, is_key);
+    let transparent_color = key_transparent_pixels(&mut pixels, is_key)?;
+
+    Ok(Image::from_parts(width, height, transparent_color, pixels.into_boxed_slice()))
+}
+
+impl Image {
+    /// RGB888のデータを`dither`で減色し、`Image`を作成します。
+    /// 
+    /// `data`は`[r, g, b]`が行優先で`width * height`個並んでいる必要があります。
+    /// 誤差拡散は32ビット整数で計算されます。
+    /// 透明色を指定する場合は[`from_rgb888_dithered_with_transparent_color`]を使用してください。
+    /// 
+    /// [`from_rgb888_dithered_with_transparent_color`]: Image::from_rgb888_dithered_with_transparent_color
+    /// 
+    /// # Errors
+    /// 
+    /// `data`の長さが足りない場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Dither, Image, Result};
+    /// # fn main() -> Result<()> {
+    /// let gradient: Vec<u8> = (0..=255).flat_map(|v| [v, v, v]).collect();
+    /// let image = Image::from_rgb888_dithered(256, 1, &gradient, Dither::FloydSteinberg)?;
+    /// 
+    /// assert_eq!(image.width(), 256);
+    /// assert_eq!(image.transparent_color(), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_rgb888_dithered(width: u16, height: u16, data: &[u8], dither: Dither) -> Result<Image> {
+        rgb888_dithered(width, height, data, None, dither)
+    }
+
+    /// RGB888のデータを`dither`で減色し、`transparent_color`を透明色とする`Image`を作成します。
+    /// 
+    /// `data`は[`from_rgb888_dithered`]と同じ形式です。
+    /// `transparent_color`と同じ色のピクセルは透明色になり、ディザリングの誤差が拡散されません。
+    /// 透明色は通常`Pixel::from_rgb(transparent_color)`になりますが、他のピクセルがその色に減色された場合は画像内で使われていない色が選ばれます。
+    /// 
+    /// [`from_rgb888_dithered`]: Image::from_rgb888_dithered
+    /// 
+    /// # Errors
+    /// 
+    /// `data`の長さが足りない場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Dither, Image, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let sprite = [255, 0, 255, 100, 100, 100];
+    /// let image = Image::from_rgb888_dithered_with_transparent_color(2, 1, &sprite, [255, 0, 255], Dither::FloydSteinberg)?;
+    /// 
+    /// assert_eq!(image.transparent_color(), Some(Pixel::MAGENTA));
+    /// assert_eq!(image[(0, 0)], Pixel::MAGENTA);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_rgb888_dithered_with_transparent_color(width: u16, height: u16, data: &[u8], transparent_color: [u8; 3], dither: Dither) -> Result<Image> {
+        rgb888_dithered(width, height, data, Some(transparent_color), dither)
+    }
+
+    /// RGBA8888のデータを`dither`で減色し、`Image`を作成します。
+    /// 
+    /// `data`は`[r, g, b, a]`が行優先で`width * height`個並んでいる必要があります。
+    /// アルファ値が`0`のピクセルは透明色になり、ディザリングの誤差が拡散されません。
+    /// 透明なピクセルの色が1色で他に使われていなければその色が、それ以外の場合は画像内で使われていない色が透明色に選ばれます。
+    /// 
+    /// # Errors
+    /// 
+    /// `data`の長さが足りない場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Dither, Image, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let data = [255, 255, 255, 0, 128, 128, 128, 255];
+    /// let image = Image::from_rgba8888_dithered(2, 1, &data, Dither::Atkinson)?;
+    /// 
+    /// assert_eq!(image.transparent_color(), Some(Pixel::WHITE));
+    /// assert_eq!(image[(0, 0)], Pixel::WHITE);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_rgba8888_dithered(width: u16, height: u16, data: &[u8], dither: Dither) -> Result<Image> {
+        let num_pixels = width as usize * height as usize;
+        if data.len() < num_pixels * 4 {
+            return Err(Error::InputBufferTooSmall);
+        }
+
+        let is_transparent = |i: usize| data[i * 4 + 3] == 0;
+        let mut pixels = self::dither(width, height, data, 4, dither, is_transparent);
+        let transparent_color = key_transparent_pixels(&mut pixels, is_transparent)?;
+
+        Ok(Image::from_parts(width, height, transparent_color, pixels.into_boxed_slice()))
+    }
+}
@@ -0,0 +1,579 @@
+//! 画像に図形を描画する関数です。
+//! 
+//! すべての関数は[`GenericImageMut`]を実装した型に描画し、画像の範囲外にはみ出す部分は切り取られます。
+//! 座標には負の値や画像より大きい値も指定できます。
+//! 
+//! # Examples
+//! 
+//! ```
+//! # use limg::{Image, Pixel, Rect};
+//! use limg::draw;
+//! 
+//! let mut image = Image::new(32, 32);
+//! draw::fill_rect(&mut image, Rect::new(4, 4, 8, 8), Pixel::BLUE);
+//! draw::line(&mut image, -10, 0, 40, 30, Pixel::WHITE);
+//! draw::circle(&mut image, 16, 16, 10, Pixel::RED);
+//! 
+//! assert_eq!(image[(4, 4)], Pixel::BLUE);
+//! assert_eq!(image[(0, 6)], Pixel::WHITE);
+//! assert_eq!(image[(26, 16)], Pixel::RED);
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::pixel::Pixel;
+use crate::rect::Rect;
+use crate::generic::GenericImageMut;
+
+/// `(x, y)`が画像の範囲内であれば`color`で塗ります。
+#[inline(always)]
+fn plot<I: GenericImageMut + ?Sized>(image: &mut I, x: i32, y: i32, color: Pixel) {
+    if x >= 0 && y >= 0 && x < image.width() as i32 && y < image.height() as i32 {
+        image.row_mut(y as u16)[x as usize] = color;
+    }
+}
+
+/// `y`行目の`x0..x1`を`color`で塗ります。
+fn hline<I: GenericImageMut + ?Sized>(image: &mut I, x0: i32, x1: i32, y: i32, color: Pixel) {
+    if y < 0 || y >= image.height() as i32 {
+        return;
+    }
+
+    let x0 = x0.clamp(0, image.width() as i32) as usize;
+    let x1 = x1.clamp(0, image.width() as i32) as usize;
+    if x0 < x1 {
+        image.row_mut(y as u16)[x0..x1].fill(color);
+    }
+}
+
+/// `dst`と`src`を`alpha / 255`の割合で混ぜます。
+/// 
+/// RGB565の各チャンネルのまま計算します。
+#[inline(always)]
+fn blend(dst: Pixel, src: Pixel, alpha: u8) -> Pixel {
+    let alpha = alpha as u32;
+    let mix = |mask: u16| {
+        let (d, s) = ((dst.0 & mask) as u32, (src.0 & mask) as u32);
+        ((s * alpha + d * (255 - alpha) + 127) / 255) as u16 & mask
+    };
+    Pixel(mix(Pixel::R_MASK) | mix(Pixel::G_MASK) | mix(Pixel::B_MASK))
+}
+
+/// `(x, y)`が画像の範囲内であれば`color`を`alpha / 255`の割合で混ぜます。
+#[inline(always)]
+fn plot_blend<I: GenericImageMut + ?Sized>(image: &mut I, x: i32, y: i32, color: Pixel, alpha: u8) {
+    if x >= 0 && y >= 0 && x < image.width() as i32 && y < image.height() as i32 {
+        let pixel = &mut image.row_mut(y as u16)[x as usize];
+        *pixel = blend(*pixel, color, alpha);
+    }
+}
+
+/// `(x0, y0)`から`(x1, y1)`まで線を描画します。
+/// 
+/// 両端のピクセルも塗られます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(4, 4);
+/// draw::line(&mut image, 0, 0, 3, 3, Pixel::WHITE);
+/// 
+/// assert_eq!(image[(2, 2)], Pixel::WHITE);
+/// assert_eq!(image[(2, 1)], Pixel::BLACK);
+/// ```
+pub fn line<I: GenericImageMut + ?Sized>(image: &mut I, x0: i32, y0: i32, x1: i32, y1: i32, color: Pixel) {
+    let (dx, dy) = (x1 as i64 - x0 as i64, y1 as i64 - y0 as i64);
+    // 長い方の軸を主軸として、主軸方向に1ピクセルずつ進める
+    let steep = dy.abs() > dx.abs();
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    let (major0, minor0, d_major, d_minor, major_size, minor_size) = if steep {
+        (y0 as i64, x0 as i64, dy, dx, height, width)
+    } else {
+        (x0 as i64, y0 as i64, dx, dy, width, height)
+    };
+    let (s_major, s_minor) = (d_major.signum(), d_minor.signum());
+    let (d_major, d_minor) = (d_major.abs(), d_minor.abs());
+    if d_major == 0 {
+        plot(image, x0, y0, color);
+        return;
+    }
+
+    // `k`歩目の副軸方向のずれは`(2 * k * d_minor + d_major) / (2 * d_major)`を切り捨てた値
+    let offset = |k: i64| ((2 * k as i128 * d_minor as i128 + d_major as i128) / (2 * d_major as i128)) as i64;
+    let minor_at = |k: i64| minor0 + s_minor * offset(k);
+
+    // 主軸方向に画像の範囲内にある歩数に切り取る
+    let (mut first, mut last) = if s_major > 0 { (-major0, major_size - 1 - major0) } else { (major0 - (major_size - 1), major0) };
+    first = first.max(0);
+    last = last.min(d_major);
+    if first > last {
+        return;
+    }
+
+    // 副軸方向の座標は歩数に対して単調なので、画像の範囲内にある歩数を二分探索で求める
+    let before = |k: i64| if s_minor < 0 { minor_at(k) >= minor_size } else { minor_at(k) < 0 };
+    let after = |k: i64| if s_minor < 0 { minor_at(k) < 0 } else { minor_at(k) >= minor_size };
+    let first = partition_point(first, last + 1, before);
+    let end = partition_point(first, last + 1, |k| !after(k));
+    if first >= end {
+        return;
+    }
+
+    // 切り取った範囲の先頭から誤差を引き継いで進める
+    let numerator = 2 * first as i128 * d_minor as i128 + d_major as i128;
+    let mut minor = minor0 + s_minor * (numerator / (2 * d_major as i128)) as i64;
+    let mut err = (numerator % (2 * d_major as i128)) as i64;
+    for k in first..end {
+        let major = major0 + s_major * k;
+        if steep {
+            plot(image, minor as i32, major as i32, color);
+        } else {
+            plot(image, major as i32, minor as i32, color);
+        }
+
+        err += 2 * d_minor;
+        if err >= 2 * d_major {
+            err -= 2 * d_major;
+            minor += s_minor;
+        }
+    }
+}
+
+/// `start..end`のうち`pred`が`true`になる先頭部分の次の値を返します。
+/// 
+/// `pred`は`start..end`で`true`の後に`false`が続く必要があります。
+fn partition_point(mut start: i64, mut end: i64, pred: impl Fn(i64) -> bool) -> i64 {
+    while start < end {
+        let mid = start + (end - start) / 2;
+        if pred(mid) {
+            start = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+    start
+}
+
+/// `(x0, y0)`から`(x1, y1)`までアンチエイリアスされた線を描画します。
+/// 
+/// Wuのアルゴリズムで、既存のピクセルとRGB565のまま混ぜ合わせます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(8, 8);
+/// draw::line_aa(&mut image, 0, 0, 7, 3, Pixel::WHITE);
+/// 
+/// assert_eq!(image[(0, 0)], Pixel::WHITE);
+/// assert_ne!(image[(1, 0)], Pixel::BLACK);
+/// assert_ne!(image[(1, 0)], Pixel::WHITE);
+/// ```
+pub fn line_aa<I: GenericImageMut + ?Sized>(image: &mut I, x0: i32, y0: i32, x1: i32, y1: i32, color: Pixel) {
+    let (x0, y0, x1, y1) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64);
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    // 長い方の軸をxとして左から右に描画する
+    let (mut x0, mut y0, mut x1, mut y1) = if steep { (y0, x0, y1, x1) } else { (x0, y0, x1, y1) };
+    if x0 > x1 {
+        core::mem::swap(&mut x0, &mut x1);
+        core::mem::swap(&mut y0, &mut y1);
+    }
+
+    let dx = x1 - x0;
+    // 傾きと交点を16.16の固定小数点で保持する
+    let gradient = if dx == 0 { 0 } else { ((y1 - y0) << 16) / dx };
+
+    // xを画像の範囲内に切り取る
+    let x_size = if steep { image.height() } else { image.width() } as i64;
+    let (first, last) = (x0.max(0), x1.min(x_size - 1));
+    if first > last {
+        return;
+    }
+    let mut intery = (y0 << 16) + gradient * (first - x0);
+
+    let plot_steep = |image: &mut I, x: i64, y: i64, alpha: u8| {
+        // 範囲外のyは`plot_blend`で切り取られる
+        let y = y.clamp(-1, i32::MAX as i64) as i32;
+        if steep {
+            plot_blend(image, y, x as i32, color, alpha);
+        } else {
+            plot_blend(image, x as i32, y, color, alpha);
+        }
+    };
+
+    for x in first..=last {
+        let y = intery >> 16;
+        let frac = ((intery >> 8) & 0xFF) as u8;
+        plot_steep(image, x, y, 255 - frac);
+        if frac != 0 {
+            plot_steep(image, x, y + 1, frac);
+        }
+        intery += gradient;
+    }
+}
+
+/// `(x0, y0)`から`(x1, y1)`まで太さ`thickness`の線を描画します。
+/// 
+/// 線は両端のピクセルを含み、両端は線に対して直角に切り取られます。`thickness`が`1`以下の場合は[`line`]と同じです。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(16, 16);
+/// draw::thick_line(&mut image, 2, 8, 13, 8, 5, Pixel::WHITE);
+/// 
+/// assert_eq!(image[(2, 6)], Pixel::WHITE);
+/// assert_eq!(image[(13, 10)], Pixel::WHITE);
+/// assert_eq!(image[(8, 11)], Pixel::BLACK);
+/// assert_eq!(image[(14, 8)], Pixel::BLACK);
+/// ```
+pub fn thick_line<I: GenericImageMut + ?Sized>(image: &mut I, x0: i32, y0: i32, x1: i32, y1: i32, thickness: u16, color: Pixel) {
+    let (dx, dy) = (x1 as i64 - x0 as i64, y1 as i64 - y0 as i64);
+    // 長さの2乗は`i64`に収まらないことがある
+    let length = (dx as i128 * dx as i128 + dy as i128 * dy as i128).isqrt() as i64;
+    if thickness <= 1 || length == 0 {
+        line(image, x0, y0, x1, y1, color);
+        return;
+    }
+
+    // 端点のピクセルの中心から、線に垂直な方向へ太さの半分、線の方向へ半ピクセルずらした4点を塗る
+    let t = thickness as i64;
+    let (nx, ny) = (-dy * t * SUBPIXEL / 2 / length, dx * t * SUBPIXEL / 2 / length);
+    let (ex, ey) = (dx * SUBPIXEL / 2 / length, dy * SUBPIXEL / 2 / length);
+    let center = |v: i32| v as i64 * SUBPIXEL + SUBPIXEL / 2;
+    let (x0, y0, x1, y1) = (center(x0) - ex, center(y0) - ey, center(x1) + ex, center(y1) + ey);
+
+    let points = [(x0 + nx, y0 + ny), (x1 + nx, y1 + ny), (x1 - nx, y1 - ny), (x0 - nx, y0 - ny)];
+    fill_polygon_subpixel(image, &points, color);
+}
+
+/// `rect`の輪郭を描画します。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, Rect, draw};
+/// let mut image = Image::new(8, 8);
+/// draw::rect(&mut image, Rect::new(1, 1, 4, 3), Pixel::WHITE);
+/// 
+/// assert_eq!(image[(4, 3)], Pixel::WHITE);
+/// assert_eq!(image[(2, 2)], Pixel::BLACK);
+/// ```
+pub fn rect<I: GenericImageMut + ?Sized>(image: &mut I, rect: Rect, color: Pixel) {
+    if rect.is_empty() {
+        return;
+    }
+
+    let (x0, y0) = (rect.x as i32, rect.y as i32);
+    let (x1, y1) = (x0 + rect.width as i32 - 1, y0 + rect.height as i32 - 1);
+
+    hline(image, x0, x1 + 1, y0, color);
+    hline(image, x0, x1 + 1, y1, color);
+    for y in y0 + 1..y1 {
+        plot(image, x0, y, color);
+        plot(image, x1, y, color);
+    }
+}
+
+/// `rect`を塗りつぶします。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, Rect, draw};
+/// let mut image = Image::new(8, 8);
+/// draw::fill_rect(&mut image, Rect::new(6, 6, 4, 4), Pixel::WHITE);
+/// 
+/// assert_eq!(image[(7, 7)], Pixel::WHITE);
+/// assert_eq!(image[(5, 5)], Pixel::BLACK);
+/// ```
+pub fn fill_rect<I: GenericImageMut + ?Sized>(image: &mut I, rect: Rect, color: Pixel) {
+    let Some(rect) = rect.intersection(&Rect::new(0, 0, image.width(), image.height())) else {
+        return;
+    };
+
+    for y in rect.y..rect.y + rect.height {
+        image.row_mut(y)[rect.x as usize..(rect.x + rect.width) as usize].fill(color);
+    }
+}
+
+/// 中心`(cx, cy)`、半径`radius`の円の輪郭を描画します。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(16, 16);
+/// draw::circle(&mut image, 8, 8, 5, Pixel::WHITE);
+/// 
+/// assert_eq!(image[(13, 8)], Pixel::WHITE);
+/// assert_eq!(image[(8, 3)], Pixel::WHITE);
+/// assert_eq!(image[(8, 8)], Pixel::BLACK);
+/// ```
+#[inline(always)]
+pub fn circle<I: GenericImageMut + ?Sized>(image: &mut I, cx: i32, cy: i32, radius: u16, color: Pixel) {
+    ellipse(image, cx, cy, radius, radius, color);
+}
+
+/// 中心`(cx, cy)`、半径`radius`の円を塗りつぶします。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(16, 16);
+/// draw::fill_circle(&mut image, 8, 8, 5, Pixel::WHITE);
+/// 
+/// assert_eq!(image[(8, 8)], Pixel::WHITE);
+/// assert_eq!(image[(12, 12)], Pixel::BLACK);
+/// ```
+#[inline(always)]
+pub fn fill_circle<I: GenericImageMut + ?Sized>(image: &mut I, cx: i32, cy: i32, radius: u16, color: Pixel) {
+    fill_ellipse(image, cx, cy, radius, radius, color);
+}
+
+/// 中心`(cx, cy)`の楕円を辿り、各ステップで上下の対称な2点`(cx ± x, cy ± y)`を`f`に渡します。
+/// 
+/// Zinglの整数アルゴリズムを使用します。`f`には`(左端, 右端, y方向の距離)`が渡されます。
+fn trace_ellipse(rx: u16, ry: u16, mut f: impl FnMut(i64, i64, i64)) {
+    let (a, b) = (rx as i64, ry as i64);
+    let (mut x, mut y) = (-a, 0i64);
+    let (a2, b2) = (a * a, b * b);
+    let mut err = x * (2 * b2 + x) + b2;
+
+    loop {
+        f(x, -x, y);
+
+        let e2 = 2 * err;
+        if e2 >= (x * 2 + 1) * b2 {
+            x += 1;
+            err += (x * 2 + 1) * b2;
+        }
+        if e2 <= (y * 2 + 1) * a2 {
+            y += 1;
+            err += (y * 2 + 1) * a2;
+        }
+        if x > 0 {
+            break;
+        }
+    }
+
+    // 平たい楕円で早く終了した場合は先端を補う
+    while y < b {
+        y += 1;
+        f(0, 0, y);
+    }
+}
+
+/// 中心`(cx, cy)`、横の半径`rx`、縦の半径`ry`の楕円の輪郭を描画します。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(16, 16);
+/// draw::ellipse(&mut image, 8, 8, 6, 3, Pixel::WHITE);
+/// 
+/// assert_eq!(image[(2, 8)], Pixel::WHITE);
+/// assert_eq!(image[(8, 5)], Pixel::WHITE);
+/// assert_eq!(image[(8, 8)], Pixel::BLACK);
+/// ```
+pub fn ellipse<I: GenericImageMut + ?Sized>(image: &mut I, cx: i32, cy: i32, rx: u16, ry: u16, color: Pixel) {
+    let Some((cx, cy, width, height)) = ellipse_bounds(image, cx, cy, rx, ry) else {
+        return;
+    };
+
+    trace_ellipse(rx, ry, |left, right, dy| {
+        for y in [cy + dy, cy - dy] {
+            if y < 0 || y >= height {
+                continue;
+            }
+            for x in [cx + left, cx + right] {
+                if x >= 0 && x < width {
+                    plot(image, x as i32, y as i32, color);
+                }
+            }
+        }
+    });
+}
+
+/// 中心`(cx, cy)`、横の半径`rx`、縦の半径`ry`の楕円を塗りつぶします。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(16, 16);
+/// draw::fill_ellipse(&mut image, 8, 8, 6, 3, Pixel::WHITE);
+/// 
+/// assert_eq!(image[(4, 9)], Pixel::WHITE);
+/// assert_eq!(image[(8, 12)], Pixel::BLACK);
+/// ```
+pub fn fill_ellipse<I: GenericImageMut + ?Sized>(image: &mut I, cx: i32, cy: i32, rx: u16, ry: u16, color: Pixel) {
+    let Some((cx, cy, width, height)) = ellipse_bounds(image, cx, cy, rx, ry) else {
+        return;
+    };
+
+    trace_ellipse(rx, ry, |left, right, dy| {
+        let x0 = (cx + left).clamp(0, width) as i32;
+        let x1 = (cx + right + 1).clamp(0, width) as i32;
+        for y in [cy + dy, cy - dy] {
+            if y >= 0 && y < height {
+                hline(image, x0, x1, y as i32, color);
+            }
+        }
+    });
+}
+
+/// 楕円の中心と画像の大きさを`i64`で返します。
+/// 
+/// `i32`の範囲の端でも溢れないように`i64`で計算します。楕円が画像と重ならない場合は`None`を返します。
+fn ellipse_bounds<I: GenericImageMut + ?Sized>(image: &I, cx: i32, cy: i32, rx: u16, ry: u16) -> Option<(i64, i64, i64, i64)> {
+    let (cx, cy, rx, ry) = (cx as i64, cy as i64, rx as i64, ry as i64);
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    if cx + rx < 0 || cx - rx >= width || cy + ry < 0 || cy - ry >= height {
+        return None;
+    }
+
+    Some((cx, cy, width, height))
+}
+
+/// `points`を順に結んだ多角形の輪郭を描画します。
+/// 
+/// 最後の点と最初の点も結ばれます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(16, 16);
+/// draw::polygon(&mut image, &[(2, 2), (12, 2), (2, 12)], Pixel::WHITE);
+/// 
+/// assert_eq!(image[(7, 2)], Pixel::WHITE);
+/// assert_eq!(image[(7, 7)], Pixel::WHITE);
+/// assert_eq!(image[(4, 4)], Pixel::BLACK);
+/// ```
+pub fn polygon<I: GenericImageMut + ?Sized>(image: &mut I, points: &[(i32, i32)], color: Pixel) {
+    for (i, &(x0, y0)) in points.iter().enumerate() {
+        let (x1, y1) = points[(i + 1) % points.len()];
+        line(image, x0, y0, x1, y1, color);
+    }
+}
+
+/// `points`を順に結んだ多角形を塗りつぶします。
+/// 
+/// 座標はピクセルの左上隅を表し、中心が多角形の内側にあるピクセルを偶奇規則で塗ります。自己交差する多角形も描画できます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, draw};
+/// let mut image = Image::new(16, 16);
+/// draw::fill_polygon(&mut image, &[(2, 2), (12, 2), (2, 12)], Pixel::WHITE);
+/// 
+/// assert_eq!(image[(4, 4)], Pixel::WHITE);
+/// assert_eq!(image[(10, 10)], Pixel::BLACK);
+/// ```
+pub fn fill_polygon<I: GenericImageMut + ?Sized>(image: &mut I, points: &[(i32, i32)], color: Pixel) {
+    let points: Vec<(i64, i64)> = points.iter().map(|&(x, y)| (x as i64 * SUBPIXEL, y as i64 * SUBPIXEL)).collect();
+    fill_polygon_subpixel(image, &points, color);
+}
+
+/// 1ピクセルの分割数
+const SUBPIXEL: i64 = 256;
+
+/// `SUBPIXEL`分の1ピクセル単位の座標で表された多角形を塗りつぶします。
+fn fill_polygon_subpixel<I: GenericImageMut + ?Sized>(image: &mut I, points: &[(i64, i64)], color: Pixel) {
+    let Some(min_y) = points.iter().map(|p| p.1).min() else {
+        return;
+    };
+    let max_y = points.iter().map(|p| p.1).max().unwrap_or(min_y);
+
+    let first = (min_y.div_euclid(SUBPIXEL)).max(0);
+    let last = (max_y.div_euclid(SUBPIXEL) + 1).min(image.height() as i64);
+
+    let mut crossings = Vec::new();
+    for y in first..last {
+        let center = y * SUBPIXEL + SUBPIXEL / 2;
+        crossings.clear();
+
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            if (y0 <= center) == (y1 <= center) {
+                continue;
+            }
+
+            // 積は`i64`に収まらないことがある
+            let x = x0 + ((center - y0) as i128 * (x1 - x0) as i128 / (y1 - y0) as i128) as i64;
+            // 中心が交点以降にある最初のピクセル
+            let px = (x - SUBPIXEL / 2 + SUBPIXEL - 1).div_euclid(SUBPIXEL);
+            crossings.push(px.clamp(i32::MIN as i64, i32::MAX as i64) as i32);
+        }
+
+        crossings.sort_unstable();
+        for span in crossings.chunks_exact(2) {
+            hline(image, span[0], span[1], y as i32, color);
+        }
+    }
+}
+
+/// `(x, y)`と同じ色で上下左右につながった領域を`color`で塗りつぶします。
+/// 
+/// `(x, y)`が画像の範囲外の場合は何もしません。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel, Rect, draw};
+/// let mut image = Image::new(16, 16);
+/// draw::rect(&mut image, Rect::new(2, 2, 8, 8), Pixel::WHITE);
+/// draw::flood_fill(&mut image, 5, 5, Pixel::RED);
+/// 
+/// assert_eq!(image[(3, 3)], Pixel::RED);
+/// assert_eq!(image[(2, 2)], Pixel::WHITE);
+/// assert_eq!(image[(12, 12)], Pixel::BLACK);
+/// ```
+pub fn flood_fill<I: GenericImageMut + ?Sized>(image: &mut I, x: i32, y: i32, color: Pixel) {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+        return;
+    }
+
+    let target = image.row(y as u16)[x as usize];
+    if target == color {
+        return;
+    }
+
+    // 横方向の連続した範囲を塗り、上下の行で塗るべき範囲の先頭を積む
+    let mut stack = vec![(x as usize, y as u16)];
+    while let Some((x, y)) = stack.pop() {
+        let row = image.row_mut(y);
+        if row[x] != target {
+            continue;
+        }
+
+        let mut left = x;
+        while left > 0 && row[left - 1] == target {
+            left -= 1;
+        }
+        let mut right = x + 1;
+        while right < width && row[right] == target {
+            right += 1;
+        }
+        row[left..right].fill(color);
+
+        for ny in [y.checked_sub(1), y.checked_add(1).filter(|&ny| (ny as usize) < height)].into_iter().flatten() {
+            let row = image.row(ny);
+            let mut inside = false;
+            for (nx, &pixel) in row.iter().enumerate().take(right).skip(left) {
+                if pixel == target && !inside {
+                    stack.push((nx, ny));
+                }
+                inside = pixel == target;
+            }
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use core::convert::Infallible;
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Point, Size};
+use embedded_graphics_core::image::{GetPixel, ImageDrawable};
+use embedded_graphics_core::pixelcolor::Rgb565;
+use embedded_graphics_core::pixelcolor::raw::{RawData, RawU16};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::image::Image;
+use crate::image_ref::ImageRef;
+use crate::image_buf::ImageBuf;
+use crate::pixel::Pixel;
+use crate::view::{ImageView, ImageViewMut};
+use crate::generic::{GenericImage, GenericImageMut};
+
+impl From<Rgb565> for Pixel {
+    #[inline(always)]
+    fn from(color: Rgb565) -> Self {
+        Pixel(RawU16::from(color).into_inner())
+    }
+}
+
+impl From<Pixel> for Rgb565 {
+    #[inline(always)]
+    fn from(pixel: Pixel) -> Self {
+        Rgb565::from(RawU16::new(pixel.0))
+    }
+}
+
+/// `area`を`width`x`height`の範囲で切り取り、`(x, y, width, height)`を返します。
+fn clip(area: &Rectangle, width: u16, height: u16) -> Option<(usize, usize, usize, usize)> {
+    let area = area.intersection(&Rectangle::new(Point::zero(), Size::new(width as u32, height as u32)));
+    if area.is_zero_sized() {
+        return None;
+    }
+
+    let Point { x, y } = area.top_left;
+    Some((x as usize, y as usize, area.size.width as usize, area.size.height as usize))
+}
+
+/// [`DrawTarget::draw_iter`]の実装です。
+fn draw_iter<T, I>(target: &mut T, pixels: I)
+where
+    T: GenericImageMut + ?Sized,
+    I: IntoIterator<Item = embedded_graphics_core::Pixel<Rgb565>>,
+{
+    let (width, height) = (target.width() as i32, target.height() as i32);
+    for embedded_graphics_core::Pixel(Point { x, y }, color) in pixels {
+        if x >= 0 && y >= 0 && x < width && y < height {
+            target.row_mut(y as u16)[x as usize] = color.into();
+        }
+    }
+}
+
+/// [`DrawTarget::fill_contiguous`]の実装です。
+fn fill_contiguous<T, I>(target: &mut T, area: &Rectangle, colors: I)
+where
+    T: GenericImageMut + ?Sized,
+    I: IntoIterator<Item = Rgb565>,
+{
+    let Some((x, y, width, height)) = clip(area, target.width(), target.height()) else {
+        return;
+    };
+
+    // `colors`は`area`全体の色なので、切り取られた部分を読み飛ばす
+    let area_width = area.size.width as usize;
+    let skip_x = (x as i64 - area.top_left.x as i64) as usize;
+    let skip_y = (y as i64 - area.top_left.y as i64) as usize;
+    let mut colors = colors.into_iter().skip(skip_y * area_width);
+
+    for row in y..y + height {
+        let line = &mut target.row_mut(row as u16)[x..x + width];
+        let mut rest = (&mut colors).skip(skip_x);
+        for (pixel, color) in line.iter_mut().zip(&mut rest) {
+            *pixel = color.into();
+        }
+        // 行の残りを読み飛ばす
+        rest.take(area_width - skip_x - width).for_each(drop);
+    }
+}
+
+/// [`DrawTarget::fill_solid`]の実装です。
+fn fill_solid<T: GenericImageMut + ?Sized>(target: &mut T, area: &Rectangle, color: Rgb565) {
+    let Some((x, y, width, height)) = clip(area, target.width(), target.height()) else {
+        return;
+    };
+
+    for row in y..y + height {
+        target.row_mut(row as u16)[x..x + width].fill(color.into());
+    }
+}
+
+/// [`ImageDrawable::draw_sub_image`]の実装です。
+/// 
+/// 透明色のピクセルは描画しません。
+fn draw_sub_image<S, D>(image: &S, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+where
+    S: GenericImage + ?Sized,
+    D: DrawTarget<Color = Rgb565>,
+{
+    let Some((x, y, width, height)) = clip(area, image.width(), image.height()) else {
+        return Ok(());
+    };
+
+    // 描画先では`area`の左上隅が原点になる
+    let offset = Point::new(x as i32, y as i32) - area.top_left;
+
+    match image.transparent_color() {
+        None => {
+            let bounds = Rectangle::new(offset, Size::new(width as u32, height as u32));
+            let colors = (y..y + height).flat_map(|row| image.row(row as u16)[x..x + width].iter().map(|&p| Rgb565::from(p)));
+            target.fill_contiguous(&bounds, colors)
+        },
+        Some(key) => {
+            let pixels = (y..y + height).flat_map(|row| {
+                image.row(row as u16)[x..x + width].iter().enumerate()
+                    .filter(move |&(_, &pixel)| pixel != key)
+                    .map(move |(col, &pixel)| {
+                        let point = offset + Point::new(col as i32, (row - y) as i32);
+                        embedded_graphics_core::Pixel(point, pixel.into())
+                    })
+            });
+            target.draw_iter(pixels)
+        },
+    }
+}
+
+impl OriginDimensions for Image {
+    #[inline(always)]
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, self.height() as u32)
+    }
+}
+
+/// `embedded-graphics`の図形やフォントを`Image`に描画します。
+/// 
+/// 範囲外のピクセルは無視されます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel};
+/// use embedded_graphics_core::prelude::*;
+/// use embedded_graphics_core::pixelcolor::Rgb565;
+/// use embedded_graphics_core::primitives::Rectangle;
+/// 
+/// let mut image = Image::new(8, 8);
+/// image.fill_solid(&Rectangle::new(Point::new(2, 2), Size::new(4, 4)), Rgb565::RED).unwrap();
+/// 
+/// assert_eq!(image[(2, 2)], Pixel::RED);
+/// assert_eq!(image[(6, 6)], Pixel::BLACK);
+/// ```
+impl DrawTarget for Image {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    #[inline]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = embedded_graphics_core::Pixel<Self::Color>>,
+    {
+        draw_iter(self, pixels);
+        Ok(())
+    }
+
+    #[inline]
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        fill_contiguous(self, area, colors);
+        Ok(())
+    }
+
+    #[inline]
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        fill_solid(self, area, color);
+        Ok(())
+    }
+
+    #[inline]
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill(color.into());
+        Ok(())
+    }
+}
+
+/// `Image`をディスプレイなどの`DrawTarget`に描画します。
+/// 
+/// 透明色のピクセルは描画されません。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Pixel};
+/// use embedded_graphics_core::prelude::*;
+/// use embedded_graphics_core::image::ImageDrawable;
+/// 
+/// let mut sprite = Image::with_transparent_color(2, 2, Pixel::MAGENTA);
+/// sprite.fill(Pixel::WHITE);
+/// sprite[(0, 0)] = Pixel::MAGENTA;
+/// 
+/// let mut display = Image::new(4, 4);
+/// display.fill(Pixel::BLUE);
+/// sprite.draw(&mut display).unwrap();
+/// 
+/// assert_eq!(display[(0, 0)], Pixel::BLUE);
+/// assert_eq!(display[(1, 1)], Pixel::WHITE);
+/// ```
+impl ImageDrawable for Image {
+    type Color = Rgb565;
+
+    #[inline]
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        draw_sub_image(self, target, &self.bounding_box())
+    }
+
+    #[inline]
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        draw_sub_image(self, target, area)
+    }
+}
+
+impl GetPixel for Image {
+    type Color = Rgb565;
+
+    #[inline]
+    fn pixel(&self, p: Point) -> Option<Self::Color> {
+        let x = u16::try_from(p.x).ok()?;
+        let y = u16::try_from(p.y).ok()?;
+        self.get_pixel(x, y).map(|&pixel| pixel.into())
+    }
+}
+
+impl<S: AsRef<[Pixel]>> OriginDimensions for ImageBuf<S> {
+    #[inline(always)]
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, self.height() as u32)
+    }
+}
+
+/// `embedded-graphics`の図形やフォントを`ImageBuf`に描画します。
+/// 
+/// 静的な配列をフレームバッファとして使用すれば、アロケーターなしで描画できます。
+impl<S: AsRef<[Pixel]> + AsMut<[Pixel]>> DrawTarget for ImageBuf<S> {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    #[inline]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = embedded_graphics_core::Pixel<Self::Color>>,
+    {
+        draw_iter(self, pixels);
+        Ok(())
+    }
+
+    #[inline]
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        fill_contiguous(self, area, colors);
+        Ok(())
+    }
+
+    #[inline]
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        fill_solid(self, area, color);
+        Ok(())
+    }
+
+    #[inline]
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill(color.into());
+        Ok(())
+    }
+}
+
+impl<S: AsRef<[Pixel]>> ImageDrawable for ImageBuf<S> {
+    type Color = Rgb565;
+
+    #[inline]
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        draw_sub_image(self, target, &self.bounding_box())
+    }
+
+    #[inline]
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        draw_sub_image(self, target, area)
+    }
+}
+
+impl<S: AsRef<[Pixel]>> GetPixel for ImageBuf<S> {
+    type Color = Rgb565;
+
+    #[inline]
+    fn pixel(&self, p: Point) -> Option<Self::Color> {
+        let x = u16::try_from(p.x).ok()?;
+        let y = u16::try_from(p.y).ok()?;
+        self.get_pixel(x, y).map(|&pixel| pixel.into())
+    }
+}
+
+impl OriginDimensions for ImageView<'_> {
+    #[inline(always)]
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, self.height() as u32)
+    }
+}
+
+impl ImageDrawable for ImageView<'_> {
+    type Color = Rgb565;
+
+    #[inline]
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        draw_sub_image(self, target, &self.bounding_box())
+    }
+
+    #[inline]
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        draw_sub_image(self, target, area)
+    }
+}
+
+impl OriginDimensions for ImageViewMut<'_> {
+    #[inline(always)]
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, self.height() as u32)
+    }
+}
+
+impl DrawTarget for ImageViewMut<'_> {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    #[inline]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = embedded_graphics_core::Pixel<Self::Color>>,
+    {
+        draw_iter(self, pixels);
+        Ok(())
+    }
+
+    #[inline]
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        fill_contiguous(self, area, colors);
+        Ok(())
+    }
+
+    #[inline]
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        fill_solid(self, area, color);
+        Ok(())
+    }
+}
+
+impl OriginDimensions for ImageRef<'_> {
+    #[inline(always)]
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, self.height() as u32)
+    }
+}
+
+/// エンコードされたバッファから直接`DrawTarget`に描画します。
+/// 
+/// 透明色のピクセルは描画されません。
+impl ImageDrawable for ImageRef<'_> {
+    type Color = Rgb565;
+
+    #[inline]
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.draw_sub_image(target, &self.bounding_box())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let Some((x, y, width, height)) = clip(area, self.width(), self.height()) else {
+            return Ok(());
+        };
+
+        let offset = Point::new(x as i32, y as i32) - area.top_left;
+        let rows = (y..y + height).map(|row| self.row(row as u16).skip(x).take(width));
+
+        match self.transparent_color() {
+            None => {
+                let bounds = Rectangle::new(offset, Size::new(width as u32, height as u32));
+                target.fill_contiguous(&bounds, rows.flatten().map(Rgb565::from))
+            },
+            Some(key) => {
+                let pixels = rows.enumerate().flat_map(|(dy, row)| {
+                    row.enumerate()
+                        .filter(move |&(_, pixel)| pixel != key)
+                        .map(move |(dx, pixel)| embedded_graphics_core::Pixel(offset + Point::new(dx as i32, dy as i32), pixel.into()))
+                });
+                target.draw_iter(pixels)
+            },
+        }
+    }
+}
+
+impl GetPixel for ImageRef<'_> {
+    type Color = Rgb565;
+
+    #[inline]
+    fn pixel(&self, p: Point) -> Option<Self::Color> {
+        let x = u16::try_from(p.x).ok()?;
+        let y = u16::try_from(p.y).ok()?;
+        self.get_pixel(x, y).map(Rgb565::from)
+    }
+}
@@ -0,0 +1,213 @@
+use crate::pixel::Pixel;
+use crate::error::{Error, Result};
+use limg_core::{ImageSpec, HEADER_SIZE, PIXEL_BYTES};
+use limg_core::encode_header;
+
+/// エンコード時に一度に変換するピクセル数
+const SCRATCH_PIXELS: usize = 128;
+
+/// [`LimgEncoder`]がバイト列を書き込む先です。
+/// 
+/// `std`機能が有効な場合、[`std::io::Write`]を実装するすべての型で実装されます。
+/// 無効な場合は`&mut [u8]`で実装されます。
+/// 
+/// [`std::io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+pub trait ByteSink {
+    /// `buf`をすべて書き込みます。
+    /// 
+    /// # Errors
+    /// 
+    /// `buf`をすべて書き込むことができない場合、`Error`を返します。
+    fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+
+    /// 書き込み済みのバイト列を出力先に反映します。
+    /// 
+    /// # Errors
+    /// 
+    /// 反映に失敗した場合、`Error`を返します。
+    #[inline(always)]
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> ByteSink for W {
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        std::io::Write::write_all(self, buf)?;
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> Result<()> {
+        std::io::Write::flush(self)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ByteSink for &mut [u8] {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        if self.len() < buf.len() {
+            return Err(Error::OutputBufferTooSmall);
+        }
+
+        let (head, tail) = ::core::mem::take(self).split_at_mut(buf.len());
+        head.copy_from_slice(buf);
+        *self = tail;
+
+        Ok(())
+    }
+}
+
+/// Limg画像を行単位でエンコードするエンコーダーです。
+/// 
+/// 作成時にヘッダーを書き込み、以降は行ごとにピクセルを受け取って小さな作業バッファで変換しながら書き込みます。
+/// 画像全体の大きさのバッファは確保しません。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, ImageSpec, LimgEncoder, Pixel, PixelEndian, Result};
+/// # fn main() -> Result<()> {
+/// let spec = ImageSpec { width: 4, height: 2, transparent_color: None, pixel_endian: PixelEndian::Little };
+/// let mut encoder = LimgEncoder::new(Vec::new(), spec)?;
+/// 
+/// encoder.write_row(&[Pixel::RED; 4])?;
+/// encoder.write_rgb888_rows(&[0, 0, 255].repeat(4))?;
+/// 
+/// let buf = encoder.finish()?;
+/// let image = Image::from_buffer(buf)?;
+/// 
+/// assert_eq!(image[(3, 0)], Pixel::RED);
+/// assert_eq!(image[(3, 1)], Pixel::BLUE);
+/// # Ok(())
+/// # }
+/// ```
+pub struct LimgEncoder<W: ByteSink> {
+    /// 書き込み先
+    sink: W,
+
+    /// ヘッダー情報
+    spec: ImageSpec,
+
+    /// 次に書き込む行
+    row: u16,
+}
+
+impl<W: ByteSink> LimgEncoder<W> {
+    /// `spec`のヘッダーを`sink`に書き込み、エンコーダーを作成します。
+    /// 
+    /// # Errors
+    /// 
+    /// ヘッダー情報が不正か書き込みに失敗した場合、`Error`を返します。
+    pub fn new(sink: W, spec: ImageSpec) -> Result<LimgEncoder<W>> {
+        let mut sink = sink;
+
+        let mut header_buf = [0u8; HEADER_SIZE];
+        encode_header(&mut header_buf, &spec)?;
+        sink.write_all(&header_buf)?;
+
+        Ok(LimgEncoder { sink, spec, row: 0 })
+    }
+
+    /// エンコードするヘッダー情報を返します。
+    #[inline(always)]
+    pub fn spec(&self) -> &ImageSpec {
+        &self.spec
+    }
+
+    /// まだ書き込んでいない行数を返します。
+    #[inline(always)]
+    pub fn remaining_rows(&self) -> u16 {
+        self.spec.height - self.row
+    }
+
+    /// 1行分のピクセルを書き込みます。
+    /// 
+    /// # Errors
+    /// 
+    /// `row`の長さが画像の幅と一致しないか書き込みに失敗した場合、`Error`を返します。
+    pub fn write_row(&mut self, row: &[Pixel]) -> Result<()> {
+        if row.len() != self.spec.width as usize {
+            return Err(Error::SizeMismatch);
+        }
+
+        self.write_rows(row)
+    }
+
+    /// 複数行分のピクセルを書き込みます。
+    /// 
+    /// `pixels`の長さは画像の幅の倍数である必要があります。
+    /// 
+    /// # Errors
+    /// 
+    /// `pixels`の長さが画像の幅の倍数でないか残りの行数を超える場合、または書き込みに失敗した場合、`Error`を返します。
+    pub fn write_rows(&mut self, pixels: &[Pixel]) -> Result<()> {
+        let rows = self.check_rows(pixels.len())?;
+
+        let mut scratch = [0u8; SCRATCH_PIXELS * PIXEL_BYTES];
+        for chunk in pixels.chunks(SCRATCH_PIXELS) {
+            for (bytes, pixel) in scratch.chunks_exact_mut(PIXEL_BYTES).zip(chunk) {
+                bytes.copy_from_slice(&pixel.to_bytes(self.spec.pixel_endian));
+            }
+            self.sink.write_all(&scratch[..chunk.len() * PIXEL_BYTES])?;
+        }
+
+        self.row += rows;
+        Ok(())
+    }
+
+    /// 複数行分のRGB888データを書き込みます。
+    /// 
+    /// 色情報はRGB565に減色されます。`data`の長さは画像の幅の3倍の倍数である必要があります。
+    /// 
+    /// # Errors
+    /// 
+    /// `data`の長さが行の倍数でないか残りの行数を超える場合、または書き込みに失敗した場合、`Error`を返します。
+    pub fn write_rgb888_rows(&mut self, data: &[u8]) -> Result<()> {
+        if !data.len().is_multiple_of(3) {
+            return Err(Error::SizeMismatch);
+        }
+        let rows = self.check_rows(data.len() / 3)?;
+
+        let mut scratch = [0u8; SCRATCH_PIXELS * PIXEL_BYTES];
+        for chunk in data.chunks(SCRATCH_PIXELS * 3) {
+            for (bytes, rgb) in scratch.chunks_exact_mut(PIXEL_BYTES).zip(chunk.chunks_exact(3)) {
+                let pixel = Pixel::from_rgb([rgb[0], rgb[1], rgb[2]]);
+                bytes.copy_from_slice(&pixel.to_bytes(self.spec.pixel_endian));
+            }
+            self.sink.write_all(&scratch[..chunk.len() / 3 * PIXEL_BYTES])?;
+        }
+
+        self.row += rows;
+        Ok(())
+    }
+
+    /// すべての行が書き込まれたことを確認し、書き込み先を返します。
+    /// 
+    /// # Errors
+    /// 
+    /// 書き込んでいない行が残っているか反映に失敗した場合、`Error`を返します。
+    pub fn finish(self) -> Result<W> {
+        let mut encoder = self;
+
+        if encoder.remaining_rows() != 0 {
+            return Err(Error::SizeMismatch);
+        }
+        encoder.sink.flush()?;
+
+        Ok(encoder.sink)
+    }
+
+    /// `num_pixels`が書き込み可能な行数に収まるか確認し、行数を返します。
+    fn check_rows(&self, num_pixels: usize) -> Result<u16> {
+        let width = self.spec.width as usize;
+        if width == 0 || !num_pixels.is_multiple_of(width) || num_pixels / width > self.remaining_rows() as usize {
+            return Err(Error::SizeMismatch);
+        }
+
+        Ok((num_pixels / width) as u16)
+    }
+}
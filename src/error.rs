@@ -19,9 +19,56 @@ pub enum Error {
     /// デコード時に発生する可能性があります。
     UnsupportedFormat,
 
+    /// データの長さが画像サイズと一致しません。
+    /// 
+    /// 行単位のエンコードで行の長さや行数が画像と合わない場合に発生します。
+    SizeMismatch,
+
+    /// フレームの大きさか透明色がアニメーションと一致しません。
+    FrameMismatch,
+
+    /// 入力データが途中で終わっています。
+    /// 
+    /// `expected`はヘッダーから求めた必要なバイト数、`actual`は実際のバイト数です。
+    Truncated {
+        /// 必要なバイト数
+        expected: usize,
+
+        /// 実際のバイト数
+        actual: usize,
+    },
+
+    /// 画像データの後に余分なデータがあります。
+    /// 
+    /// [`DecodeOptions`]で余分なデータを許可していない場合に発生します。
+    /// 
+    /// [`DecodeOptions`]: crate::DecodeOptions
+    TrailingData {
+        /// 画像データのバイト数
+        expected: usize,
+
+        /// 実際のバイト数
+        actual: usize,
+    },
+
+    /// ヘッダーのフィールドが不正です。
+    InvalidHeader {
+        /// 不正なフィールド
+        field: HeaderField,
+
+        /// フィールドの先頭からのバイト位置
+        offset: usize,
+    },
+
     /// IOエラー
     #[cfg(feature = "std")]
-    IoError(std::io::Error)
+    IoError(std::io::Error),
+
+    /// PNG画像のデコードエラー
+    /// 
+    /// IO操作の失敗は[`Error::IoError`]になります。
+    #[cfg(feature = "png")]
+    PngError(png::DecodingError),
 }
 
 impl ::core::fmt::Display for Error {
@@ -31,12 +78,52 @@ impl ::core::fmt::Display for Error {
             Error::InputBufferTooSmall => limg_core::Error::InputBufferTooSmall.fmt(f),
             Error::OutputBufferTooSmall => limg_core::Error::OutputBufferTooSmall.fmt(f),
             Error::UnsupportedFormat => limg_core::Error::UnsupportedFormat.fmt(f),
+            Error::SizeMismatch => f.write_str("データの長さが画像サイズと一致しません"),
+            Error::FrameMismatch => f.write_str("フレームの大きさか透明色がアニメーションと一致しません"),
+            Error::Truncated { expected, actual } => write!(f, "入力データが途中で終わっています（必要: {expected}バイト、実際: {actual}バイト）"),
+            Error::TrailingData { expected, actual } => write!(f, "画像データの後に余分なデータがあります（画像: {expected}バイト、実際: {actual}バイト）"),
+            Error::InvalidHeader { field, offset } => write!(f, "ヘッダーの{field}が不正です（オフセット: {offset}）"),
             #[cfg(feature = "std")]
             Error::IoError(err) => err.fmt(f),
+            #[cfg(feature = "png")]
+            Error::PngError(err) => err.fmt(f),
         }
     }
 }
 
+/// 不正なヘッダーのフィールドです。
+/// 
+/// [`Error::InvalidHeader`]で使用されます。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HeaderField {
+    /// シグネチャ
+    Signature,
+
+    /// バージョン
+    Version,
+
+    /// フラグ
+    Flags,
+
+    /// 画像の幅
+    Width,
+
+    /// 画像の高さ
+    Height,
+}
+
+impl ::core::fmt::Display for HeaderField {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            HeaderField::Signature => "シグネチャ",
+            HeaderField::Version => "バージョン",
+            HeaderField::Flags => "フラグ",
+            HeaderField::Width => "幅",
+            HeaderField::Height => "高さ",
+        })
+    }
+}
+
 impl From<limg_core::Error> for Error {
     fn from(err: limg_core::Error) -> Self {
         match err {
@@ -55,4 +142,24 @@ impl From<std::io::Error> for Error {
     }
 }
 
+#[cfg(feature = "png")]
+impl From<png::DecodingError> for Error {
+    fn from(err: png::DecodingError) -> Self {
+        match err {
+            png::DecodingError::IoError(err) => Error::IoError(err),
+            err => Error::PngError(err),
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<png::EncodingError> for Error {
+    fn from(err: png::EncodingError) -> Self {
+        match err {
+            png::EncodingError::IoError(err) => Error::IoError(err),
+            _ => Error::UnsupportedFormat,
+        }
+    }
+}
+
 impl ::core::error::Error for Error {}
\ No newline at end of file
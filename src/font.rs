@@ -0,0 +1,497 @@
+use core::str::Lines;
+
+use crate::image::Image;
+use crate::pixel::Pixel;
+use crate::rect::Rect;
+use crate::generic::GenericImageMut;
+
+/// 組み込みの5x7フォントのグリフデータ
+/// 
+/// `' '`から`'~'`までの95文字を、1文字あたり5列で並べています。各列の最下位ビットが最上段です。
+const BUILTIN_5X7: [u8; 95 * 5] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, // ' '
+    0x00, 0x00, 0x5F, 0x00, 0x00, // '!'
+    0x00, 0x07, 0x00, 0x07, 0x00, // '"'
+    0x14, 0x7F, 0x14, 0x7F, 0x14, // '#'
+    0x24, 0x2A, 0x7F, 0x2A, 0x12, // '$'
+    0x23, 0x13, 0x08, 0x64, 0x62, // '%'
+    0x36, 0x49, 0x55, 0x22, 0x50, // '&'
+    0x00, 0x05, 0x03, 0x00, 0x00, // '''
+    0x00, 0x1C, 0x22, 0x41, 0x00, // '('
+    0x00, 0x41, 0x22, 0x1C, 0x00, // ')'
+    0x08, 0x2A, 0x1C, 0x2A, 0x08, // '*'
+    0x08, 0x08, 0x3E, 0x08, 0x08, // '+'
+    0x00, 0x50, 0x30, 0x00, 0x00, // ','
+    0x08, 0x08, 0x08, 0x08, 0x08, // '-'
+    0x00, 0x60, 0x60, 0x00, 0x00, // '.'
+    0x20, 0x10, 0x08, 0x04, 0x02, // '/'
+    0x3E, 0x51, 0x49, 0x45, 0x3E, // '0'
+    0x00, 0x42, 0x7F, 0x40, 0x00, // '1'
+    0x42, 0x61, 0x51, 0x49, 0x46, // '2'
+    0x21, 0x41, 0x45, 0x4B, 0x31, // '3'
+    0x18, 0x14, 0x12, 0x7F, 0x10, // '4'
+    0x27, 0x45, 0x45, 0x45, 0x39, // '5'
+    0x3C, 0x4A, 0x49, 0x49, 0x30, // '6'
+    0x01, 0x71, 0x09, 0x05, 0x03, // '7'
+    0x36, 0x49, 0x49, 0x49, 0x36, // '8'
+    0x06, 0x49, 0x49, 0x29, 0x1E, // '9'
+    0x00, 0x36, 0x36, 0x00, 0x00, // ':'
+    0x00, 0x56, 0x36, 0x00, 0x00, // ';'
+    0x08, 0x14, 0x22, 0x41, 0x00, // '<'
+    0x14, 0x14, 0x14, 0x14, 0x14, // '='
+    0x00, 0x41, 0x22, 0x14, 0x08, // '>'
+    0x02, 0x01, 0x51, 0x09, 0x06, // '?'
+    0x32, 0x49, 0x79, 0x41, 0x3E, // '@'
+    0x7E, 0x11, 0x11, 0x11, 0x7E, // 'A'
+    0x7F, 0x49, 0x49, 0x49, 0x36, // 'B'
+    0x3E, 0x41, 0x41, 0x41, 0x22, // 'C'
+    0x7F, 0x41, 0x41, 0x22, 0x1C, // 'D'
+    0x7F, 0x49, 0x49, 0x49, 0x41, // 'E'
+    0x7F, 0x09, 0x09, 0x01, 0x01, // 'F'
+    0x3E, 0x41, 0x41, 0x51, 0x32, // 'G'
+    0x7F, 0x08, 0x08, 0x08, 0x7F, // 'H'
+    0x00, 0x41, 0x7F, 0x41, 0x00, // 'I'
+    0x20, 0x40, 0x41, 0x3F, 0x01, // 'J'
+    0x7F, 0x08, 0x14, 0x22, 0x41, // 'K'
+    0x7F, 0x40, 0x40, 0x40, 0x40, // 'L'
+    0x7F, 0x02, 0x04, 0x02, 0x7F, // 'M'
+    0x7F, 0x04, 0x08, 0x10, 0x7F, // 'N'
+    0x3E, 0x41, 0x41, 0x41, 0x3E, // 'O'
+    0x7F, 0x09, 0x09, 0x09, 0x06, // 'P'
+    0x3E, 0x41, 0x51, 0x21, 0x5E, // 'Q'
+    0x7F, 0x09, 0x19, 0x29, 0x46, // 'R'
+    0x46, 0x49, 0x49, 0x49, 0x31, // 'S'
+    0x01, 0x01, 0x7F, 0x01, 0x01, // 'T'
+    0x3F, 0x40, 0x40, 0x40, 0x3F, // 'U'
+    0x1F, 0x20, 0x40, 0x20, 0x1F, // 'V'
+    0x7F, 0x20, 0x18, 0x20, 0x7F, // 'W'
+    0x63, 0x14, 0x08, 0x14, 0x63, // 'X'
+    0x03, 0x04, 0x78, 0x04, 0x03, // 'Y'
+    0x61, 0x51, 0x49, 0x45, 0x43, // 'Z'
+    0x00, 0x7F, 0x41, 0x41, 0x00, // '['
+    0x02, 0x04, 0x08, 0x10, 0x20, // '\'
+    0x00, 0x41, 0x41, 0x7F, 0x00, // ']'
+    0x04, 0x02, 0x01, 0x02, 0x04, // '^'
+    0x40, 0x40, 0x40, 0x40, 0x40, // '_'
+    0x00, 0x01, 0x02, 0x04, 0x00, // '`'
+    0x20, 0x54, 0x54, 0x54, 0x78, // 'a'
+    0x7F, 0x48, 0x44, 0x44, 0x38, // 'b'
+    0x38, 0x44, 0x44, 0x44, 0x20, // 'c'
+    0x38, 0x44, 0x44, 0x48, 0x7F, // 'd'
+    0x38, 0x54, 0x54, 0x54, 0x18, // 'e'
+    0x08, 0x7E, 0x09, 0x01, 0x02, // 'f'
+    0x0C, 0x52, 0x52, 0x52, 0x3E, // 'g'
+    0x7F, 0x08, 0x04, 0x04, 0x78, // 'h'
+    0x00, 0x44, 0x7D, 0x40, 0x00, // 'i'
+    0x20, 0x40, 0x44, 0x3D, 0x00, // 'j'
+    0x7F, 0x10, 0x28, 0x44, 0x00, // 'k'
+    0x00, 0x41, 0x7F, 0x40, 0x00, // 'l'
+    0x7C, 0x04, 0x18, 0x04, 0x78, // 'm'
+    0x7C, 0x08, 0x04, 0x04, 0x78, // 'n'
+    0x38, 0x44, 0x44, 0x44, 0x38, // 'o'
+    0x7C, 0x14, 0x14, 0x14, 0x08, // 'p'
+    0x08, 0x14, 0x14, 0x18, 0x7C, // 'q'
+    0x7C, 0x08, 0x04, 0x04, 0x08, // 'r'
+    0x48, 0x54, 0x54, 0x54, 0x20, // 's'
+    0x04, 0x3F, 0x44, 0x40, 0x20, // 't'
+    0x3C, 0x40, 0x40, 0x20, 0x7C, // 'u'
+    0x1C, 0x20, 0x40, 0x20, 0x1C, // 'v'
+    0x3C, 0x40, 0x30, 0x40, 0x3C, // 'w'
+    0x44, 0x28, 0x10, 0x28, 0x44, // 'x'
+    0x0C, 0x50, 0x50, 0x50, 0x3C, // 'y'
+    0x44, 0x64, 0x54, 0x4C, 0x44, // 'z'
+    0x00, 0x08, 0x36, 0x41, 0x00, // '{'
+    0x00, 0x00, 0x7F, 0x00, 0x00, // '|'
+    0x00, 0x41, 0x36, 0x08, 0x00, // '}'
+    0x08, 0x04, 0x08, 0x10, 0x08, // '~'
+];
+
+/// フォントのグリフの大きさと文字送りです。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GlyphMetrics {
+    /// グリフの幅
+    pub width: u16,
+
+    /// グリフの高さ
+    pub height: u16,
+
+    /// 次の文字までの横方向の距離
+    pub advance: u16,
+
+    /// 次の行までの縦方向の距離
+    pub line_height: u16,
+}
+
+/// グリフデータの格納方法
+#[derive(Clone, Copy, Debug)]
+enum Glyphs<'a> {
+    /// 1文字あたり`width`バイトの列データ
+    Columns(&'static [u8]),
+
+    /// 透明色で抜かれたグリフを格子状に並べた画像
+    Atlas { image: &'a Image, columns: u16 },
+}
+
+/// 等幅のビットマップフォントです。
+/// 
+/// 組み込みの[`Font::BUILTIN_5X7`]か、グリフを並べた画像から[`Font::from_atlas`]で作成します。
+/// 描画には[`GenericImageMut::draw_text`]を使用します。
+#[derive(Clone, Copy, Debug)]
+pub struct Font<'a> {
+    glyphs: Glyphs<'a>,
+    metrics: GlyphMetrics,
+
+    /// 最初のグリフの文字コード
+    first: u32,
+
+    /// グリフの数
+    count: u32,
+}
+
+impl Font<'static> {
+    /// 組み込みの5x7のASCIIフォントです。
+    /// 
+    /// `' '`から`'~'`までの文字を含み、文字送りは6ピクセル、行送りは8ピクセルです。`no_std`環境でも使用できます。
+    pub const BUILTIN_5X7: Font<'static> = Font {
+        glyphs: Glyphs::Columns(&BUILTIN_5X7),
+        metrics: GlyphMetrics { width: 5, height: 7, advance: 6, line_height: 8 },
+        first: ' ' as u32,
+        count: 95,
+    };
+}
+
+impl<'a> Font<'a> {
+    /// グリフを格子状に並べた画像からフォントを作成します。
+    /// 
+    /// `atlas`は左上から行優先に`first`から続く文字のグリフを`glyph_width`x`glyph_height`の大きさで並べた画像です。
+    /// 透明色でないピクセルがグリフとして描画されます。文字送りはグリフの幅+1、行送りはグリフの高さ+1になります。
+    /// 
+    /// `atlas`に透明色がないか、グリフの大きさが0か`atlas`より大きい場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Font, GenericImageMut, Image, Pixel};
+    /// // 'A'と'B'の2x2のグリフ
+    /// let mut atlas = Image::with_transparent_color(4, 2, Pixel::BLACK);
+    /// atlas[(0, 0)] = Pixel::WHITE;
+    /// atlas[(3, 1)] = Pixel::WHITE;
+    /// let font = Font::from_atlas(&atlas, 2, 2, 'A').unwrap();
+    /// 
+    /// let mut image = Image::new(8, 4);
+    /// image.draw_text(0, 0, "BA", &font, Pixel::RED);
+    /// 
+    /// assert_eq!(image[(1, 1)], Pixel::RED);
+    /// assert_eq!(image[(3, 0)], Pixel::RED);
+    /// ```
+    pub fn from_atlas(atlas: &'a Image, glyph_width: u16, glyph_height: u16, first: char) -> Option<Font<'a>> {
+        if atlas.transparent_color().is_none() || glyph_width == 0 || glyph_height == 0 {
+            return None;
+        }
+
+        let columns = atlas.width() / glyph_width;
+        let rows = atlas.height() / glyph_height;
+        if columns == 0 || rows == 0 {
+            return None;
+        }
+
+        Some(Font {
+            glyphs: Glyphs::Atlas { image: atlas, columns },
+            metrics: GlyphMetrics {
+                width: glyph_width,
+                height: glyph_height,
+                advance: glyph_width.saturating_add(1),
+                line_height: glyph_height.saturating_add(1),
+            },
+            first: first as u32,
+            count: columns as u32 * rows as u32,
+        })
+    }
+
+    /// 文字送りと行送りを変更したフォントを返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Font;
+    /// let font = Font::BUILTIN_5X7.with_spacing(5, 10);
+    /// 
+    /// assert_eq!(font.metrics().advance, 5);
+    /// assert_eq!(font.metrics().line_height, 10);
+    /// ```
+    #[inline(always)]
+    pub const fn with_spacing(mut self, advance: u16, line_height: u16) -> Font<'a> {
+        self.metrics.advance = advance;
+        self.metrics.line_height = line_height;
+        self
+    }
+
+    /// グリフの大きさと文字送りを返します。
+    #[inline(always)]
+    pub const fn metrics(&self) -> GlyphMetrics {
+        self.metrics
+    }
+
+    /// `c`のグリフがある場合`true`を返します。
+    /// 
+    /// グリフがない文字は`'?'`で、`'?'`もない場合は空白で描画されます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Font;
+    /// assert!(Font::BUILTIN_5X7.has_glyph('A'));
+    /// assert!(!Font::BUILTIN_5X7.has_glyph('あ'));
+    /// ```
+    #[inline(always)]
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.glyph_index(c).is_some()
+    }
+
+    #[inline(always)]
+    fn glyph_index(&self, c: char) -> Option<u32> {
+        (c as u32).checked_sub(self.first).filter(|&i| i < self.count)
+    }
+
+    /// グリフの`(x, y)`のピクセルが塗られる場合`true`を返します。
+    fn is_set(&self, index: u32, x: u16, y: u16) -> bool {
+        match self.glyphs {
+            Glyphs::Columns(data) => {
+                let column = data[index as usize * self.metrics.width as usize + x as usize];
+                column >> y & 1 != 0
+            },
+            Glyphs::Atlas { image, columns } => {
+                let gx = (index % columns as u32) as u16 * self.metrics.width;
+                let gy = (index / columns as u32) as u16 * self.metrics.height;
+                Some(image[(gx + x, gy + y)]) != image.transparent_color()
+            },
+        }
+    }
+
+    /// 1行の文字数が`n`のときの幅を返します。
+    #[inline(always)]
+    fn line_width(&self, n: usize) -> u32 {
+        match n {
+            0 => 0,
+            n => (n as u32 - 1) * self.metrics.advance as u32 + self.metrics.width as u32,
+        }
+    }
+
+    /// `lines`行のときの高さを返します。
+    #[inline(always)]
+    fn lines_height(&self, lines: usize) -> u32 {
+        match lines {
+            0 => 0,
+            n => (n as u32 - 1) * self.metrics.line_height as u32 + self.metrics.height as u32,
+        }
+    }
+
+    /// 幅`width`に収まる1行の文字数を返します。
+    /// 
+    /// 1文字も収まらない場合も、折り返しが進むよう`1`を返します。
+    fn columns_in(&self, width: u16) -> usize {
+        let GlyphMetrics { width: glyph, advance, .. } = self.metrics;
+        if width <= glyph || advance == 0 {
+            1
+        } else {
+            ((width - glyph) / advance) as usize + 1
+        }
+    }
+
+    /// `text`を描画したときの`(幅, 高さ)`を返します。
+    /// 
+    /// `text`は改行で複数行に分けられます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Font;
+    /// let font = Font::BUILTIN_5X7;
+    /// 
+    /// assert_eq!(font.measure("abc"), (17, 7));
+    /// assert_eq!(font.measure("abc\nde"), (17, 15));
+    /// assert_eq!(font.measure(""), (0, 0));
+    /// ```
+    pub fn measure(&self, text: &str) -> (u32, u32) {
+        let (width, lines) = text.lines().fold((0, 0), |(width, lines), line| {
+            (width.max(self.line_width(line.chars().count())), lines + 1)
+        });
+        (width, self.lines_height(lines))
+    }
+
+    /// 幅`max_width`で折り返して`text`を描画したときの`(幅, 高さ)`を返します。
+    /// 
+    /// 折り返しは[`GenericImageMut::draw_text_wrapped`]と同じです。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Font;
+    /// let font = Font::BUILTIN_5X7;
+    /// 
+    /// assert_eq!(font.measure_wrapped("hello world", 40), (29, 15));
+    /// ```
+    pub fn measure_wrapped(&self, text: &str, max_width: u16) -> (u32, u32) {
+        let (width, lines) = self.wrap(text, max_width).fold((0, 0), |(width, lines), line| {
+            (width.max(self.line_width(line.chars().count())), lines + 1)
+        });
+        (width, self.lines_height(lines))
+    }
+
+    /// `text`を幅`max_width`で折り返した各行を返します。
+    fn wrap<'t>(&self, text: &'t str, max_width: u16) -> WrapLines<'t> {
+        WrapLines {
+            lines: text.lines(),
+            rest: None,
+            columns: self.columns_in(max_width),
+        }
+    }
+}
+
+/// 空白で単語を区切り、1行が`columns`文字以内になるよう折り返します。
+/// 
+/// 1行に収まらない単語は文字単位で折り返します。
+struct WrapLines<'t> {
+    lines: Lines<'t>,
+
+    /// 折り返し中の段落の残り
+    rest: Option<&'t str>,
+
+    columns: usize,
+}
+
+impl<'t> Iterator for WrapLines<'t> {
+    type Item = &'t str;
+
+    fn next(&mut self) -> Option<&'t str> {
+        let rest = match self.rest.take() {
+            Some(rest) => rest,
+            None => self.lines.next()?,
+        };
+
+        // 収まる場合はそのまま返す
+        let Some((limit, c)) = rest.char_indices().nth(self.columns) else {
+            return Some(rest);
+        };
+
+        // `columns`文字目の直後までで最後の空白で折り返す
+        let space = if c == ' ' { Some(limit) } else { rest[..limit].rfind(' ') };
+        let (line, next) = match space {
+            Some(space) if !rest[..space].trim_end().is_empty() => (rest[..space].trim_end(), &rest[space..]),
+            _ => (&rest[..limit], &rest[limit..]),
+        };
+
+        let next = next.trim_start_matches(' ');
+        if !next.is_empty() {
+            self.rest = Some(next);
+        }
+        Some(line)
+    }
+}
+
+/// `text`の1行を`(x, y)`から`clip`の範囲内に描画します。
+fn draw_line<I: GenericImageMut + ?Sized>(image: &mut I, x: i32, y: i32, text: &str, font: &Font, color: Pixel, clip: Rect) {
+    let GlyphMetrics { width, height, advance, .. } = font.metrics;
+    // `i32`の範囲の端でも溢れないように`i64`で計算する
+    let (x, y) = (x as i64, y as i64);
+    let (left, top) = (clip.x as i64, clip.y as i64);
+    let (right, bottom) = (left + clip.width as i64, top + clip.height as i64);
+
+    if y >= bottom || y + height as i64 <= top {
+        return;
+    }
+
+    let fallback = font.glyph_index('?');
+    for (i, c) in text.chars().enumerate() {
+        let gx = x + i as i64 * advance as i64;
+        if gx >= right {
+            break;
+        }
+        if gx + width as i64 <= left {
+            continue;
+        }
+        let Some(index) = font.glyph_index(c).or(fallback) else {
+            continue;
+        };
+
+        for py in 0..height {
+            let sy = y + py as i64;
+            if sy < top || sy >= bottom {
+                continue;
+            }
+            let row = image.row_mut(sy as u16);
+            for px in 0..width {
+                let sx = gx + px as i64;
+                if sx >= left && sx < right && font.is_set(index, px, py) {
+                    row[sx as usize] = color;
+                }
+            }
+        }
+    }
+}
+
+impl Image {
+    /// `font`で`text`を`(x, y)`を左上隅として描画します。
+    /// 
+    /// [`GenericImageMut::draw_text`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Font, Image, Pixel};
+    /// let mut image = Image::new(32, 16);
+    /// image.draw_text(1, 1, "Hi!", &Font::BUILTIN_5X7, Pixel::WHITE);
+    /// 
+    /// assert_eq!(image[(1, 4)], Pixel::WHITE);
+    /// ```
+    #[inline(always)]
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, font: &Font, color: Pixel) {
+        GenericImageMut::draw_text(self, x, y, text, font, color);
+    }
+
+    /// `font`で`text`を`bounds`の幅で折り返して描画します。
+    /// 
+    /// [`GenericImageMut::draw_text_wrapped`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Font, Image, Pixel, Rect};
+    /// let mut image = Image::new(64, 32);
+    /// let font = Font::BUILTIN_5X7;
+    /// image.draw_text_wrapped(Rect::new(0, 0, 40, 32), "hello world", &font, Pixel::WHITE);
+    /// 
+    /// let mut expected = Image::new(64, 32);
+    /// expected.draw_text(0, 0, "hello\nworld", &font, Pixel::WHITE);
+    /// assert_eq!(image, expected);
+    /// ```
+    #[inline(always)]
+    pub fn draw_text_wrapped(&mut self, bounds: Rect, text: &str, font: &Font, color: Pixel) {
+        GenericImageMut::draw_text_wrapped(self, bounds, text, font, color);
+    }
+}
+
+/// [`GenericImageMut::draw_text`]の実装です。
+pub(crate) fn draw_text<I: GenericImageMut + ?Sized>(image: &mut I, x: i32, y: i32, text: &str, font: &Font, color: Pixel) {
+    let clip = Rect::new(0, 0, image.width(), image.height());
+    for (i, line) in text.lines().enumerate() {
+        let ly = y.saturating_add((i as i32).saturating_mul(font.metrics.line_height as i32));
+        draw_line(image, x, ly, line, font, color, clip);
+    }
+}
+
+/// [`GenericImageMut::draw_text_wrapped`]の実装です。
+pub(crate) fn draw_text_wrapped<I: GenericImageMut + ?Sized>(image: &mut I, bounds: Rect, text: &str, font: &Font, color: Pixel) {
+    let Some(clip) = bounds.intersection(&Rect::new(0, 0, image.width(), image.height())) else {
+        return;
+    };
+
+    let bottom = bounds.y as i32 + bounds.height as i32;
+    for (i, line) in font.wrap(text, bounds.width).enumerate() {
+        let y = bounds.y as i32 + i as i32 * font.metrics.line_height as i32;
+        if y >= bottom {
+            break;
+        }
+        draw_line(image, bounds.x as i32, y, line, font, color, clip);
+    }
+}
@@ -0,0 +1,595 @@
+use alloc::vec::Vec;
+
+use crate::font::Font;
+use crate::image::Image;
+use crate::image_buf::ImageBuf;
+use crate::pixel::Pixel;
+use crate::rect::Rect;
+use crate::patch::ImagePatch;
+use crate::error::Result;
+
+/// 画像として読み取り可能な型の共通トレイトです。
+/// 
+/// [`Image`]と[`ImageBuf`]、[`ImageView`]、[`ImageViewMut`]で実装されており、画像処理関数はこのトレイトを通してどれでも受け取れます。
+/// 
+/// [`ImageBuf`]: crate::ImageBuf
+/// [`ImageView`]: crate::ImageView
+/// [`ImageViewMut`]: crate::ImageViewMut
+pub trait GenericImage {
+    /// 画像の幅を返します。
+    fn width(&self) -> u16;
+
+    /// 画像の高さを返します。
+    fn height(&self) -> u16;
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    fn transparent_color(&self) -> Option<Pixel>;
+
+    /// `y`行目のピクセルのスライスを返します。
+    /// 
+    /// # Panics
+    /// 
+    /// `y`が高さ以上の場合パニックします。
+    fn row(&self, y: u16) -> &[Pixel];
+
+    /// 上の行から順にピクセルのスライスを返す`Iterator`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 1)] = Pixel::WHITE;
+    /// 
+    /// let rows: Vec<&[Pixel]> = image.rows().collect();
+    /// 
+    /// assert_eq!(rows.len(), 2);
+    /// assert_eq!(rows[1][0], Pixel::WHITE);
+    /// ```
+    fn rows(&self) -> impl Iterator<Item = &[Pixel]> {
+        (0..self.height()).map(move |y| self.row(y))
+    }
+
+    /// 画像のピクセルをコピーして新しい`Image`を作成します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel, Rect};
+    /// let mut sheet = Image::new(16, 16);
+    /// sheet[(8, 8)] = Pixel::RED;
+    /// 
+    /// let sprite = sheet.view(Rect::new(8, 8, 8, 8)).unwrap().to_image();
+    /// 
+    /// assert_eq!(sprite.width(), 8);
+    /// assert_eq!(sprite[(0, 0)], Pixel::RED);
+    /// ```
+    fn to_image(&self) -> Image {
+        let mut pixels = Vec::with_capacity(self.width() as usize * self.height() as usize);
+        for row in self.rows() {
+            pixels.extend_from_slice(row);
+        }
+
+        Image::from_parts(self.width(), self.height(), self.transparent_color(), pixels.into_boxed_slice())
+    }
+
+    /// 左右反転した新しい`Image`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// let flipped = image.flipped_horizontal();
+    /// 
+    /// assert_eq!(flipped[(2, 0)], Pixel::RED);
+    /// ```
+    fn flipped_horizontal(&self) -> Image {
+        let mut image = self.to_image();
+        image.flip_horizontal();
+        image
+    }
+
+    /// 上下反転した新しい`Image`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// let flipped = image.flipped_vertical();
+    /// 
+    /// assert_eq!(flipped[(0, 1)], Pixel::RED);
+    /// ```
+    fn flipped_vertical(&self) -> Image {
+        let mut image = self.to_image();
+        image.flip_vertical();
+        image
+    }
+
+    /// 時計回りに90度回転した新しい`Image`を返します。
+    /// 
+    /// 幅と高さが入れ替わります。透明色は引き継がれます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel, Rect};
+    /// let mut sheet = Image::with_transparent_color(8, 8, Pixel::MAGENTA);
+    /// sheet[(4, 0)] = Pixel::RED;
+    /// 
+    /// let rotated = sheet.view(Rect::new(4, 0, 4, 2)).unwrap().rotated90();
+    /// 
+    /// assert_eq!((rotated.width(), rotated.height()), (2, 4));
+    /// assert_eq!(rotated[(1, 0)], Pixel::RED);
+    /// assert_eq!(rotated.transparent_color(), Some(Pixel::MAGENTA));
+    /// ```
+    fn rotated90(&self) -> Image {
+        crate::transform::remap(self, crate::transform::Remap::Rotate90)
+    }
+
+    /// 180度回転した新しい`Image`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// let rotated = image.rotated180();
+    /// 
+    /// assert_eq!(rotated[(2, 1)], Pixel::RED);
+    /// ```
+    fn rotated180(&self) -> Image {
+        let mut image = self.to_image();
+        image.rotate180();
+        image
+    }
+
+    /// 時計回りに270度(反時計回りに90度)回転した新しい`Image`を返します。
+    /// 
+    /// 幅と高さが入れ替わります。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// let rotated = image.rotated270();
+    /// 
+    /// assert_eq!(rotated[(0, 2)], Pixel::RED);
+    /// ```
+    fn rotated270(&self) -> Image {
+        crate::transform::remap(self, crate::transform::Remap::Rotate270)
+    }
+
+    /// 左上から右下への対角線で反転した新しい`Image`を返します。
+    /// 
+    /// 幅と高さが入れ替わります。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(2, 1)] = Pixel::RED;
+    /// 
+    /// let transposed = image.transposed();
+    /// 
+    /// assert_eq!(transposed[(1, 2)], Pixel::RED);
+    /// ```
+    fn transposed(&self) -> Image {
+        crate::transform::remap(self, crate::transform::Remap::Transpose)
+    }
+
+    /// 最近傍補間で`width`x`height`に拡大縮小した新しい`Image`を返します。
+    /// 
+    /// 色を混ぜないため透明色はそのまま保たれます。`no_std`環境でも使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel};
+    /// let mut image = Image::new(2, 2);
+    /// image[(1, 1)] = Pixel::RED;
+    /// 
+    /// let resized = image.resize_nearest(4, 4);
+    /// 
+    /// assert_eq!(resized[(1, 1)], Pixel::BLACK);
+    /// assert_eq!(resized[(2, 2)], Pixel::RED);
+    /// assert_eq!(resized[(3, 3)], Pixel::RED);
+    /// ```
+    fn resize_nearest(&self, width: u16, height: u16) -> Image {
+        crate::resize::resize_nearest(self, width, height)
+    }
+
+    /// `filter`で`width`x`height`に拡大縮小した新しい`Image`を返します。
+    /// 
+    /// 補間は線形RGBで計算されます。透明色のピクセルはマスクとして扱われ、周囲の色に混ざりません。
+    /// 結果の不透明度が半分未満のピクセルは透明色になります。補間で透明色と同じ色が生じた場合は、別の色が透明色に選ばれます。
+    /// ただし、不透明なピクセルがRGB565のすべての色を使っている場合は元の透明色がそのまま使われ、透明色と同じ色に補間された不透明なピクセルも透明になります。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Filter, GenericImage, Image, Pixel};
+    /// let mut image = Image::with_transparent_color(8, 8, Pixel::MAGENTA);
+    /// image.fill(Pixel::WHITE);
+    /// image[(0, 0)] = Pixel::MAGENTA;
+    /// 
+    /// let thumbnail = image.resize(4, 4, Filter::Lanczos3);
+    /// 
+    /// assert_eq!(thumbnail[(3, 3)], Pixel::WHITE);
+    /// assert_eq!(thumbnail.transparent_color(), Some(Pixel::MAGENTA));
+    /// ```
+    #[cfg(feature = "std")]
+    fn resize(&self, width: u16, height: u16, filter: crate::resize::Filter) -> Image {
+        crate::resize::resize(self, width, height, filter)
+    }
+
+    /// `other`と異なるピクセルを囲む矩形を返します。
+    /// 
+    /// 間隔が8ピクセル以下の矩形は1つに結合されます。
+    /// 結合の間隔を指定する場合は[`diff_with_threshold`]を使用してください。
+    /// 
+    /// [`diff_with_threshold`]: GenericImage::diff_with_threshold
+    /// 
+    /// # Panics
+    /// 
+    /// `other`と大きさが異なる場合パニックします。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel, Rect};
+    /// let old = Image::new(64, 64);
+    /// let mut new = old.clone();
+    /// new[(2, 2)] = Pixel::RED;
+    /// new[(6, 3)] = Pixel::RED;
+    /// new[(50, 50)] = Pixel::RED;
+    /// 
+    /// assert_eq!(old.diff(&new), vec![Rect::new(2, 2, 5, 2), Rect::new(50, 50, 1, 1)]);
+    /// assert!(old.diff(&old).is_empty());
+    /// ```
+    #[inline(always)]
+    fn diff(&self, other: &impl GenericImage) -> Vec<Rect> {
+        crate::patch::diff(self, other, crate::patch::DEFAULT_THRESHOLD)
+    }
+
+    /// `other`と異なるピクセルを囲む矩形を返します。
+    /// 
+    /// 縦横どちらの間隔も`threshold`ピクセル以下の矩形は1つに結合されます。
+    /// 大きくすると矩形の数が減る代わりに、変化していないピクセルも含まれるようになります。
+    /// 
+    /// # Panics
+    /// 
+    /// `other`と大きさが異なる場合パニックします。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImage, Image, Pixel, Rect};
+    /// let old = Image::new(64, 64);
+    /// let mut new = old.clone();
+    /// new[(2, 2)] = Pixel::RED;
+    /// new[(6, 3)] = Pixel::RED;
+    /// 
+    /// assert_eq!(old.diff_with_threshold(&new, 0).len(), 2);
+    /// assert_eq!(old.diff_with_threshold(&new, 3), vec![Rect::new(2, 2, 5, 2)]);
+    /// ```
+    #[inline(always)]
+    fn diff_with_threshold(&self, other: &impl GenericImage, threshold: u16) -> Vec<Rect> {
+        crate::patch::diff(self, other, threshold)
+    }
+}
+
+/// 画像として書き込み可能な型の共通トレイトです。
+pub trait GenericImageMut: GenericImage {
+    /// `y`行目のピクセルの可変スライスを返します。
+    /// 
+    /// # Panics
+    /// 
+    /// `y`が高さ以上の場合パニックします。
+    fn row_mut(&mut self, y: u16) -> &mut [Pixel];
+
+    /// 指定した色で画像を塗りつぶします。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImageMut, Image, Pixel, Rect};
+    /// let mut image = Image::new(4, 4);
+    /// image.view_mut(Rect::new(1, 1, 2, 2)).unwrap().fill(Pixel::WHITE);
+    /// 
+    /// assert_eq!(image[(0, 0)], Pixel::BLACK);
+    /// assert_eq!(image[(2, 2)], Pixel::WHITE);
+    /// ```
+    fn fill(&mut self, pixel: Pixel) {
+        for y in 0..self.height() {
+            self.row_mut(y).fill(pixel);
+        }
+    }
+
+    /// `src`を`(dst_x, dst_y)`を左上隅として画像に描画します。
+    /// 
+    /// `src`の透明色と同じピクセルは描画されません。
+    /// 画像の範囲外にはみ出す部分は切り取られ、負の座標も指定できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImageMut, Image, Pixel};
+    /// let mut image = Image::new(4, 4);
+    /// let mut sprite = Image::with_transparent_color(2, 2, Pixel::MAGENTA);
+    /// sprite.fill(Pixel::WHITE);
+    /// sprite[(0, 0)] = Pixel::MAGENTA;
+    /// 
+    /// image.blit(&sprite, -1, 3);
+    /// 
+    /// assert_eq!(image[(0, 3)], Pixel::WHITE);
+    /// assert_eq!(image[(1, 3)], Pixel::BLACK);
+    /// ```
+    #[inline(always)]
+    fn blit(&mut self, src: &impl GenericImage, dst_x: i32, dst_y: i32) {
+        self.blit_region(src, Rect::new(0, 0, src.width(), src.height()), dst_x, dst_y);
+    }
+
+    /// `src`の`region`の範囲を`(dst_x, dst_y)`を左上隅として画像に描画します。
+    /// 
+    /// `src`の透明色と同じピクセルは描画されません。
+    /// `region`は`src`の範囲に、描画先は画像の範囲に切り取られます。
+    /// 透明色がない場合は行単位でコピーされます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImageMut, Image, Pixel, Rect};
+    /// let mut image = Image::new(4, 4);
+    /// let mut sheet = Image::new(8, 8);
+    /// sheet[(4, 4)] = Pixel::RED;
+    /// 
+    /// image.blit_region(&sheet, Rect::new(4, 4, 4, 4), 0, 0);
+    /// 
+    /// assert_eq!(image[(0, 0)], Pixel::RED);
+    /// ```
+    fn blit_region(&mut self, src: &impl GenericImage, region: Rect, dst_x: i32, dst_y: i32) {
+        crate::blit::blit_region(self, src, region, dst_x, dst_y);
+    }
+
+    /// 画像をその場で左右反転します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImageMut, Image, Pixel, Rect};
+    /// let mut image = Image::new(4, 1);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// image.view_mut(Rect::new(0, 0, 2, 1)).unwrap().flip_horizontal();
+    /// 
+    /// assert_eq!(image[(1, 0)], Pixel::RED);
+    /// ```
+    fn flip_horizontal(&mut self) {
+        crate::transform::flip_horizontal(self);
+    }
+
+    /// 画像をその場で上下反転します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImageMut, Image, Pixel};
+    /// let mut image = Image::new(1, 3);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// image.flip_vertical();
+    /// 
+    /// assert_eq!(image[(0, 2)], Pixel::RED);
+    /// ```
+    fn flip_vertical(&mut self) {
+        crate::transform::flip_vertical(self);
+    }
+
+    /// 画像をその場で180度回転します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImageMut, Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// image.rotate180();
+    /// 
+    /// assert_eq!(image[(2, 1)], Pixel::RED);
+    /// ```
+    fn rotate180(&mut self) {
+        self.flip_vertical();
+        self.flip_horizontal();
+    }
+
+    /// `font`で`text`を`(x, y)`を左上隅として描画します。
+    /// 
+    /// `text`は改行で複数行に分けられます。画像の範囲外にはみ出す部分は切り取られます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Font, GenericImageMut, Image, Pixel};
+    /// let mut image = Image::new(32, 16);
+    /// image.draw_text(1, 1, "Hi!\nOK", &Font::BUILTIN_5X7, Pixel::WHITE);
+    /// 
+    /// // 'H'の左端の縦線
+    /// assert_eq!(image[(1, 4)], Pixel::WHITE);
+    /// // 2行目の'O'の上端
+    /// assert_eq!(image[(2, 9)], Pixel::WHITE);
+    /// ```
+    fn draw_text(&mut self, x: i32, y: i32, text: &str, font: &Font, color: Pixel) {
+        crate::font::draw_text(self, x, y, text, font, color);
+    }
+
+    /// `font`で`text`を`bounds`の幅で折り返して描画します。
+    /// 
+    /// 空白で単語を区切って折り返し、1行に収まらない単語は文字単位で折り返します。
+    /// `bounds`からはみ出す部分は描画されません。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Font, GenericImageMut, Image, Pixel, Rect};
+    /// let mut image = Image::new(64, 32);
+    /// let font = Font::BUILTIN_5X7;
+    /// image.draw_text_wrapped(Rect::new(0, 0, 40, 32), "hello world", &font, Pixel::WHITE);
+    /// 
+    /// // "world"は2行目に描画される
+    /// let mut expected = Image::new(64, 32);
+    /// expected.draw_text(0, 0, "hello\nworld", &font, Pixel::WHITE);
+    /// assert_eq!(image, expected);
+    /// ```
+    fn draw_text_wrapped(&mut self, bounds: Rect, text: &str, font: &Font, color: Pixel) {
+        crate::font::draw_text_wrapped(self, bounds, text, font, color);
+    }
+
+    /// `patch`の各領域のピクセルを画像に書き込みます。
+    /// 
+    /// # Errors
+    /// 
+    /// `patch`の適用先と画像の大きさが異なる場合、`Error::SizeMismatch`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImageMut, Image, ImagePatch, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut display = Image::new(16, 16);
+    /// let mut frame = display.clone();
+    /// frame[(4, 4)] = Pixel::GREEN;
+    /// 
+    /// display.apply_patch(&ImagePatch::new(&display, &frame))?;
+    /// 
+    /// assert_eq!(display, frame);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    fn apply_patch(&mut self, patch: &ImagePatch) -> Result<()> {
+        crate::patch::apply_patch(self, patch)
+    }
+}
+
+impl GenericImage for Image {
+    #[inline(always)]
+    fn width(&self) -> u16 {
+        Image::width(self)
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u16 {
+        Image::height(self)
+    }
+
+    #[inline(always)]
+    fn transparent_color(&self) -> Option<Pixel> {
+        Image::transparent_color(self)
+    }
+
+    #[inline(always)]
+    fn row(&self, y: u16) -> &[Pixel] {
+        assert!(y < self.height());
+        let width = self.width() as usize;
+        &self.pixels()[y as usize * width..(y as usize + 1) * width]
+    }
+
+    #[inline(always)]
+    fn to_image(&self) -> Image {
+        self.clone()
+    }
+}
+
+impl GenericImageMut for Image {
+    #[inline(always)]
+    fn row_mut(&mut self, y: u16) -> &mut [Pixel] {
+        assert!(y < self.height());
+        let width = self.width() as usize;
+        &mut self.pixels_mut()[y as usize * width..(y as usize + 1) * width]
+    }
+
+    #[inline(always)]
+    fn fill(&mut self, pixel: Pixel) {
+        Image::fill(self, pixel);
+    }
+
+    fn flip_vertical(&mut self) {
+        let width = self.width() as usize;
+        let half = self.height() as usize / 2 * width;
+        if half == 0 {
+            return;
+        }
+
+        let pixels = self.pixels_mut();
+        let bottom_start = pixels.len() - half;
+        let (top, rest) = pixels.split_at_mut(half);
+        let bottom = &mut rest[bottom_start - half..];
+
+        for (a, b) in top.chunks_exact_mut(width).zip(bottom.chunks_exact_mut(width).rev()) {
+            a.swap_with_slice(b);
+        }
+    }
+
+    #[inline(always)]
+    fn rotate180(&mut self) {
+        self.pixels_mut().reverse();
+    }
+}
+
+impl<S: AsRef<[Pixel]>> GenericImage for ImageBuf<S> {
+    #[inline(always)]
+    fn width(&self) -> u16 {
+        ImageBuf::width(self)
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u16 {
+        ImageBuf::height(self)
+    }
+
+    #[inline(always)]
+    fn transparent_color(&self) -> Option<Pixel> {
+        ImageBuf::transparent_color(self)
+    }
+
+    #[inline(always)]
+    fn row(&self, y: u16) -> &[Pixel] {
+        assert!(y < self.height());
+        let width = self.width() as usize;
+        &self.pixels()[y as usize * width..(y as usize + 1) * width]
+    }
+}
+
+impl<S: AsRef<[Pixel]> + AsMut<[Pixel]>> GenericImageMut for ImageBuf<S> {
+    #[inline(always)]
+    fn row_mut(&mut self, y: u16) -> &mut [Pixel] {
+        assert!(y < self.height());
+        let width = self.width() as usize;
+        &mut self.pixels_mut()[y as usize * width..(y as usize + 1) * width]
+    }
+
+    #[inline(always)]
+    fn fill(&mut self, pixel: Pixel) {
+        ImageBuf::fill(self, pixel);
+    }
+
+    #[inline(always)]
+    fn rotate180(&mut self) {
+        self.pixels_mut().reverse();
+    }
+}
@@ -0,0 +1,131 @@
+use limg_core::{ImageSpec, PixelEndian, HEADER_SIZE};
+
+use crate::error::{Error, HeaderField, Result};
+
+// limg-coreはシグネチャやフラグを公開していないため、ヘッダーの解析はこのモジュールにまとめる
+
+/// Limg画像のシグネチャ
+pub(crate) const SIGNATURE: [u8; 4] = *b"LIMG";
+
+/// 対応しているLimg画像のバージョン
+pub(crate) const VERSION: u8 = 1;
+
+/// ピクセルデータがリトルエンディアンであることを示すフラグ
+pub(crate) const FLAG_LITTLE_ENDIAN: u8 = 0b01;
+
+/// 透明色が指定されていることを示すフラグ
+pub(crate) const FLAG_TRANSPARENT: u8 = 0b10;
+
+/// シグネチャの位置
+const SIGNATURE_OFFSET: usize = 0;
+
+/// バージョンの位置
+const VERSION_OFFSET: usize = 4;
+
+/// フラグの位置
+pub(crate) const FLAGS_OFFSET: usize = 5;
+
+/// 画像の幅の位置
+const WIDTH_OFFSET: usize = 6;
+
+/// 画像の高さの位置
+const HEIGHT_OFFSET: usize = 8;
+
+/// 透明色の位置
+const TRANSPARENT_COLOR_OFFSET: usize = 10;
+
+/// ヘッダーの解析で見つかった問題です。
+/// 
+/// `const fn`で扱えるよう、`Error`とは別に定義しています。
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum HeaderError {
+    /// ヘッダーが[`HEADER_SIZE`]バイトに満たない
+    Truncated,
+
+    /// 不正なフィールド
+    Invalid(HeaderField),
+}
+
+impl HeaderError {
+    /// 長さ`len`のバッファを解析したときの`Error`に変換します。
+    pub(crate) fn into_error(self, len: usize) -> Error {
+        match self {
+            HeaderError::Truncated => Error::Truncated { expected: HEADER_SIZE, actual: len },
+            HeaderError::Invalid(field) => Error::InvalidHeader { field, offset: field_offset(field) },
+        }
+    }
+}
+
+/// ヘッダーでの`field`の位置を返します。
+const fn field_offset(field: HeaderField) -> usize {
+    match field {
+        HeaderField::Signature => SIGNATURE_OFFSET,
+        HeaderField::Version => VERSION_OFFSET,
+        HeaderField::Flags => FLAGS_OFFSET,
+        HeaderField::Width => WIDTH_OFFSET,
+        HeaderField::Height => HEIGHT_OFFSET,
+    }
+}
+
+/// Limg画像のヘッダーを検証してデコードします。
+/// 
+/// 不正な場合は問題のあるフィールドとその位置を含む`Error`を返します。
+#[inline]
+pub(crate) fn check_header(buf: &[u8]) -> Result<ImageSpec> {
+    parse_header(buf).map_err(|err| err.into_error(buf.len()))
+}
+
+/// Limg画像のヘッダーを解析します。
+/// 
+/// [`check_header`]と[`ImageRef::from_bytes`]で共有するため`const fn`になっています。
+/// 
+/// [`ImageRef::from_bytes`]: crate::ImageRef::from_bytes
+#[inline]
+pub(crate) const fn parse_header(buf: &[u8]) -> core::result::Result<ImageSpec, HeaderError> {
+    parse_common(buf, &SIGNATURE, VERSION, FLAG_LITTLE_ENDIAN | FLAG_TRANSPARENT)
+}
+
+/// シグネチャ、バージョン、フラグの未定義ビットを検証し、先頭[`HEADER_SIZE`]バイトを解析します。
+/// 
+/// Limg画像と同じ先頭を持つ圧縮画像の解析にも使用します。
+pub(crate) const fn parse_common(buf: &[u8], signature: &[u8; 4], version: u8, known_flags: u8) -> core::result::Result<ImageSpec, HeaderError> {
+    if buf.len() < HEADER_SIZE {
+        return Err(HeaderError::Truncated);
+    }
+
+    let mut i = 0;
+    while i < signature.len() {
+        if buf[SIGNATURE_OFFSET + i] != signature[i] {
+            return Err(HeaderError::Invalid(HeaderField::Signature));
+        }
+        i += 1;
+    }
+    if buf[VERSION_OFFSET] != version {
+        return Err(HeaderError::Invalid(HeaderField::Version));
+    }
+
+    let flags = buf[FLAGS_OFFSET];
+    if flags & !known_flags != 0 {
+        return Err(HeaderError::Invalid(HeaderField::Flags));
+    }
+
+    let width = u16::from_le_bytes([buf[WIDTH_OFFSET], buf[WIDTH_OFFSET + 1]]);
+    if width == 0 {
+        return Err(HeaderError::Invalid(HeaderField::Width));
+    }
+    let height = u16::from_le_bytes([buf[HEIGHT_OFFSET], buf[HEIGHT_OFFSET + 1]]);
+    if height == 0 {
+        return Err(HeaderError::Invalid(HeaderField::Height));
+    }
+
+    Ok(ImageSpec {
+        width,
+        height,
+        transparent_color: if flags & FLAG_TRANSPARENT != 0 {
+            Some(u16::from_le_bytes([buf[TRANSPARENT_COLOR_OFFSET], buf[TRANSPARENT_COLOR_OFFSET + 1]]))
+        } else {
+            None
+        },
+        pixel_endian: if flags & FLAG_LITTLE_ENDIAN != 0 { PixelEndian::Little } else { PixelEndian::Big },
+    })
+}
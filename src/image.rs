@@ -1,13 +1,22 @@
 use alloc::boxed::Box;
 use alloc::vec;
+use alloc::vec::Vec;
 
 use crate::pixel::Pixel;
+use crate::dither::Dither;
+#[cfg(feature = "std")]
+use crate::decoder::LimgDecoder;
+#[cfg(feature = "std")]
+use crate::encoder::LimgEncoder;
+use crate::compress::{self, Compression};
+use crate::header;
+use crate::decoder::DecodeOptions;
 use crate::error::Result;
 use core::ops::{Index, IndexMut};
 use core::slice::{from_raw_parts, from_raw_parts_mut};
 use limg_core::{ImageSpec, ColorType, PixelEndian, HEADER_SIZE, PIXEL_BYTES};
-use limg_core::{decode_header, decode_data, decoded_size};
-use limg_core::{encode_header, encode_data, encoded_size};
+use limg_core::{decode_data, decoded_size};
+use limg_core::{encode_header, encode_data};
 
 #[inline(always)]
 const fn image_index(x: u16, y: u16, width: u16) -> usize {
@@ -37,6 +46,15 @@ pub struct Image {
 }
 
 impl Image {
+    /// 各フィールドを指定してLimg画像を作成します。
+    /// 
+    /// `pixels`の長さは`width * height`である必要があります。
+    #[inline(always)]
+    pub(crate) fn from_parts(width: u16, height: u16, transparent_color: Option<Pixel>, pixels: Box<[Pixel]>) -> Image {
+        debug_assert_eq!(pixels.len(), width as usize * height as usize);
+        Image { width, height, transparent_color, pixels }
+    }
+
     /// `width`と`height`を指定してLimg画像を作成します。
     /// 
     /// 透明色なしの黒で初期化されます。
@@ -177,10 +195,11 @@ impl Image {
     /// image[(0, 0)] = Pixel::WHITE;
     /// 
     /// assert_eq!(image.get_pixel(0, 0), Some(Pixel::WHITE).as_ref());
+    /// assert_eq!(image.get_pixel(0, 50), None);
     /// ```
     #[inline(always)]
     pub fn get_pixel(&self, x: u16, y: u16) -> Option<&Pixel> {
-        if x < self.width || y < self.height {
+        if x < self.width && y < self.height {
             Some(unsafe { self.pixels.get_unchecked(image_index(x, y, self.width)) })
         } else {
             None
@@ -224,10 +243,11 @@ impl Image {
     /// *image.get_pixel_mut(0, 0).unwrap() = Pixel::WHITE;
     /// 
     /// assert_eq!(image[(0, 0)], Pixel::WHITE);
+    /// assert!(image.get_pixel_mut(100, 0).is_none());
     /// ```
     #[inline(always)]
     pub fn get_pixel_mut(&mut self, x:u16, y: u16) -> Option<&mut Pixel> {
-        if x < self.width || y < self.height {
+        if x < self.width && y < self.height {
             Some(unsafe { self.pixels.get_unchecked_mut(image_index(x, y, self.width)) })
         } else {
             None
@@ -307,11 +327,105 @@ impl Image {
         self.pixels.fill(pixel);
     }
 
+    /// RGB888のデータから`Image`を作成します。
+    /// 
+    /// `data`は`[r, g, b]`が行優先で`width * height`個並んでいる必要があります。
+    /// 色情報はRGB565に減色されます。ディザリングする場合は[`from_rgb888_dithered`]を使用してください。
+    /// 
+    /// [`from_rgb888_dithered`]: Image::from_rgb888_dithered
+    /// 
+    /// # Errors
+    /// 
+    /// `data`の長さが足りない場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let image = Image::from_rgb888(2, 1, &[255, 0, 0, 0, 255, 0])?;
+    /// 
+    /// assert_eq!(image.pixels(), &[Pixel::RED, Pixel::GREEN]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn from_rgb888(width: u16, height: u16, data: &[u8]) -> Result<Image> {
+        Image::from_rgb888_dithered(width, height, data, Dither::None)
+    }
+
+    /// RGBA8888のデータから`Image`を作成します。
+    /// 
+    /// `data`は`[r, g, b, a]`が行優先で`width * height`個並んでいる必要があります。
+    /// 色情報はRGB565に減色され、アルファ値が`0`のピクセルは透明色になります。
+    /// 透明なピクセルの色が1色で他に使われていなければその色が、それ以外の場合は画像内で使われていない色が透明色に選ばれます。
+    /// 
+    /// # Errors
+    /// 
+    /// `data`の長さが足りない場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let image = Image::from_rgba8888(2, 1, &[255, 0, 0, 255, 0, 0, 0, 0])?;
+    /// 
+    /// assert_eq!(image.transparent_color(), Some(Pixel::BLACK));
+    /// assert_eq!(image.pixels(), &[Pixel::RED, Pixel::BLACK]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn from_rgba8888(width: u16, height: u16, data: &[u8]) -> Result<Image> {
+        Image::from_rgba8888_dithered(width, height, data, Dither::None)
+    }
+
+    /// 画像をRGB888のデータに変換します。
+    /// 
+    /// `[r, g, b]`が行優先で並んだ`Vec`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::new(2, 1);
+    /// image[(1, 0)] = Pixel::BLUE;
+    /// 
+    /// assert_eq!(image.to_rgb888_vec(), vec![0, 0, 0, 0, 0, 255]);
+    /// ```
+    pub fn to_rgb888_vec(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|p| p.into_rgb()).collect()
+    }
+
+    /// 画像をRGBA8888のデータに変換します。
+    /// 
+    /// `[r, g, b, a]`が行優先で並んだ`Vec`を返します。透明色のピクセルはアルファ値`0`、それ以外は`255`になります。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::with_transparent_color(2, 1, Pixel::BLACK);
+    /// image[(1, 0)] = Pixel::BLUE;
+    /// 
+    /// assert_eq!(image.to_rgba8888_vec(), vec![0, 0, 0, 0, 0, 0, 255, 255]);
+    /// ```
+    pub fn to_rgba8888_vec(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|&p| {
+            let [r, g, b] = p.into_rgb();
+            [r, g, b, if Some(p) == self.transparent_color { 0x00 } else { 0xFF }]
+        }).collect()
+    }
+
     /// `buf`から画像を読み取り、`Image`を作成します。
     /// 
+    /// 圧縮された画像も読み取れます。画像データの後の余分なデータは無視されます。
+    /// 
     /// # Errors
     /// 
     /// 画像データが不正な場合、`Error`を返します。
+    /// ピクセルデータが不足している場合は`Error::Truncated`になります。
     /// 
     /// # Examples
     /// 
@@ -323,23 +437,76 @@ impl Image {
     /// # Ok(())
     /// # }
     /// ```
+    #[inline(always)]
     pub fn from_buffer(buf: impl AsRef<[u8]>) -> Result<Image> {
+        Image::from_buffer_with_options(buf, DecodeOptions::default())
+    }
+
+    /// `buf`から`options`に従って画像を読み取り、`Image`を作成します。
+    /// 
+    /// 圧縮された画像も読み取れます。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正な場合、`Error`を返します。
+    /// ヘッダーが不正な場合は`Error::InvalidHeader`、`options`で許可されていないデータの不足や余分なデータは
+    /// `Error::Truncated`や`Error::TrailingData`になります。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{DecodeOptions, Error, HeaderField, Image, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut buf = vec![0u8; 12 + 2 * 2 * 2];
+    /// Image::new(2, 2).to_buffer(&mut buf)?;
+    /// buf[4] = 9;
+    /// 
+    /// let err = Image::from_buffer_with_options(&buf, DecodeOptions::STRICT).unwrap_err();
+    /// assert!(matches!(err, Error::InvalidHeader { field: HeaderField::Version, offset: 4 }));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_buffer_with_options(buf: impl AsRef<[u8]>, options: DecodeOptions) -> Result<Image> {
         let buf = buf.as_ref();
 
+        if compress::is_compressed(buf) {
+            let header = compress::decode_header(buf)?;
+            let mut pixels = vec![Pixel::BLACK; header.spec.num_pixels()].into_boxed_slice();
+            compress::decode_into(buf, &mut pixels, &options)?;
+            return Ok(Image {
+                width: header.spec.width,
+                height: header.spec.height,
+                transparent_color: header.spec.transparent_color.map(Pixel),
+                pixels,
+            });
+        }
+
         // ヘッダーのデコード
-        let spec = decode_header(&buf)?;
-
-        // ピクセルデータデコード
-        let pixels_size = decoded_size(&spec, ColorType::Rgb565);
-        let mut pixels = Box::<[Pixel]>::new_uninit_slice(pixels_size);
-        let pixels_slice = unsafe { from_raw_parts_mut(pixels.as_mut_ptr().cast::<u8>(), pixels_size) };
-        decode_data(&buf[HEADER_SIZE..], pixels_slice, &spec, ColorType::Rgb565)?;
-        
+        let spec = header::check_header(buf)?;
+        let num_pixels = spec.num_pixels();
+        let end = HEADER_SIZE + num_pixels * PIXEL_BYTES;
+
+        let pixels = if options.check_length(end, buf.len())? {
+            // 読み込めたピクセル以外は黒で埋める
+            let mut pixels = vec![Pixel::BLACK; num_pixels].into_boxed_slice();
+            for (pixel, bytes) in pixels.iter_mut().zip(buf[HEADER_SIZE..].chunks_exact(PIXEL_BYTES)) {
+                *pixel = Pixel::from_bytes([bytes[0], bytes[1]], spec.pixel_endian);
+            }
+            pixels
+        } else {
+            // ピクセルデータデコード
+            let pixels_size = decoded_size(&spec, ColorType::Rgb565);
+            let mut pixels = Box::<[Pixel]>::new_uninit_slice(num_pixels);
+            let pixels_slice = unsafe { from_raw_parts_mut(pixels.as_mut_ptr().cast::<u8>(), pixels_size) };
+            decode_data(&buf[HEADER_SIZE..end], pixels_slice, &spec, ColorType::Rgb565)?;
+            unsafe { pixels.assume_init() }
+        };
+
         Ok(Image {
             width: spec.width,
             height: spec.height,
-            transparent_color: spec.transparent_color.map(|color| Pixel(color)),
-            pixels: unsafe { pixels.assume_init() }
+            transparent_color: spec.transparent_color.map(Pixel),
+            pixels,
         })
     }
 
@@ -403,6 +570,54 @@ impl Image {
 
         Ok(())
     }
+
+    /// 画像のピクセルを`compression`で圧縮してエンコードします。
+    /// 
+    /// ピクセルはリトルエンディアンで書き込まれます。
+    /// 圧縮された画像は[`from_buffer`]や[`LimgDecoder`]で読み取れますが、圧縮に対応していないデコーダーでは`Error::UnsupportedFormat`になります。
+    /// 
+    /// [`from_buffer`]: Image::from_buffer
+    /// [`LimgDecoder`]: crate::LimgDecoder
+    /// 
+    /// # Errors
+    /// 
+    /// 画像サイズが0の場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Compression, Image, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut image = Image::new(64, 64);
+    /// image.fill(Pixel::BLUE);
+    /// 
+    /// let buf = image.to_compressed(Compression::Rle16)?;
+    /// 
+    /// assert!(buf.len() < 64 * 64 * 2);
+    /// assert_eq!(Image::from_buffer(&buf)?, image);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn to_compressed(&self, compression: Compression) -> Result<Vec<u8>> {
+        self.to_compressed_with_endian(compression, PixelEndian::Little)
+    }
+
+    /// 画像のピクセルを指定された`endian`で`compression`で圧縮してエンコードします。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像サイズが0の場合、`Error`を返します。
+    pub fn to_compressed_with_endian(&self, compression: Compression, endian: PixelEndian) -> Result<Vec<u8>> {
+        let spec = ImageSpec {
+            width: self.width,
+            height: self.height,
+            transparent_color: self.transparent_color.map(|p| p.0),
+            pixel_endian: endian
+        };
+
+        compress::encode(&self.pixels, &spec, compression)
+    }
 }
 
 impl Index<(u16, u16)> for Image {
@@ -428,6 +643,7 @@ impl Image {
     /// # Errors
     /// 
     /// 画像データが不正かIO操作に失敗した場合、`Error`を返します。
+    /// ファイルが途中で終わっている場合は、圧縮の有無にかかわらず`Error::Truncated`になります。
     /// 
     /// # Examples
     /// 
@@ -439,14 +655,17 @@ impl Image {
     /// # }
     #[inline(always)]
     pub fn open(path: impl AsRef<std::path::Path>) -> Result<Image> {
-        Image::from_read(std::fs::File::open(path)?)
+        Image::from_read(std::io::BufReader::new(std::fs::File::open(path)?))
     }
 
     /// `reader`から画像を読み取り、`Image`を作成します。
     /// 
+    /// 圧縮された画像も読み取れます。画像データの後は読み込みません。
+    /// 
     /// # Errors
     /// 
     /// 画像データが不正かIO操作に失敗した場合、`Error`を返します。
+    /// データが途中で終わっている場合は、圧縮の有無にかかわらず`Error::Truncated`になります。
     /// 
     /// # Examples
     /// 
@@ -458,31 +677,35 @@ impl Image {
     /// # Ok(())
     /// # }
     pub fn from_read(reader: impl std::io::Read) -> Result<Image> {
-        let mut reader = reader;
-        
-        // ヘッダーのデコード
-        let mut header_buf = [0u8; HEADER_SIZE];
-        reader.read_exact(&mut header_buf)?;
-        let spec = decode_header(&header_buf)?;
-
-        // バイナリピクセルデータ読み込み
-        let data_size = spec.num_pixels() * PIXEL_BYTES;
-        let mut data = Box::<[u8]>::new_uninit_slice(spec.num_pixels() * PIXEL_BYTES);
-        let data_slice = unsafe { from_raw_parts_mut(data.as_mut_ptr().cast::<u8>(), data_size) };
-        reader.read_exact(data_slice)?;
-
-        // ピクセルデータデコード
-        let pixels_size = decoded_size(&spec, ColorType::Rgb565);
-        let mut pixels = Box::<[Pixel]>::new_uninit_slice(pixels_size / ColorType::Rgb565.bytes_per_pixel());
-        let pixels_slice = unsafe { from_raw_parts_mut(pixels.as_mut_ptr().cast::<u8>(), pixels_size) };
-        decode_data(data_slice, pixels_slice, &spec, ColorType::Rgb565)?;
-        
-        Ok(Image {
-            width: spec.width,
-            height: spec.height,
-            transparent_color: spec.transparent_color.map(|c| Pixel(c)),
-            pixels: unsafe { pixels.assume_init() }
-        })
+        LimgDecoder::new(reader)?.read_image()
+    }
+
+    /// `reader`から`options`に従って画像を読み取り、`Image`を作成します。
+    /// 
+    /// 不足したデータを補うか余分なデータを検出するため、`options`がデフォルトでない場合は`reader`を最後まで読み込みます。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正かIO操作に失敗した場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,no_run
+    /// # use limg::{DecodeOptions, Image, Result};
+    /// # fn main() -> Result<()> {
+    /// let reader = std::fs::File::open("image.limg")?;
+    /// let image = Image::from_read_with_options(reader, DecodeOptions::STRICT)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_read_with_options(mut reader: impl std::io::Read, options: DecodeOptions) -> Result<Image> {
+        if options == DecodeOptions::default() {
+            return Image::from_read(reader);
+        }
+
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        Image::from_buffer_with_options(buf, options)
     }
 
     /// 画像をエンコードし`path`に保存します。既にファイルが存在する場合上書きします。
@@ -531,6 +754,39 @@ impl Image {
         self.to_write_with_endian(&mut file, endian)
     }
 
+    /// 画像のピクセルを`compression`で圧縮して`path`に保存します。既にファイルが存在する場合上書きします。
+    /// 
+    /// ピクセルはリトルエンディアンで書き込まれます。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正かIO操作に失敗した場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,no_run
+    /// # use limg::{Compression, Image, Result};
+    /// # fn main() -> Result<()> {
+    /// # let image = Image::new(10, 10);
+    /// image.save_compressed("image.limg", Compression::Rle16)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn save_compressed(&self, path: impl AsRef<std::path::Path>, compression: Compression) -> Result<()> {
+        self.save_compressed_with_endian(path, compression, PixelEndian::Little)
+    }
+
+    /// 画像のピクセルを指定された`endian`で`compression`で圧縮して`path`に保存します。既にファイルが存在する場合上書きします。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正かIO操作に失敗した場合、`Error`を返します。
+    pub fn save_compressed_with_endian(&self, path: impl AsRef<std::path::Path>, compression: Compression, endian: PixelEndian) -> Result<()> {
+        std::fs::write(path, self.to_compressed_with_endian(compression, endian)?)?;
+        Ok(())
+    }
+
     /// 画像をエンコードし`writer`に書き込みます。
     /// 
     /// ピクセルはリトルエンディアンで書き込まれます。
@@ -580,20 +836,10 @@ impl Image {
             pixel_endian: endian
         };
 
-        // バッファの用意
-        let buf_size = encoded_size(&spec);
-        let mut encoded_buf = Box::<[u8]>::new_uninit_slice(buf_size);
-        let buf_slice = unsafe { from_raw_parts_mut(encoded_buf.as_mut_ptr().cast::<u8>(), buf_size) };
-        let data_slice = unsafe { from_raw_parts(self.pixels.as_ptr().cast::<u8>(), spec.num_pixels() * PIXEL_BYTES) };
-
-        // 画像のエンコード
-        encode_header(buf_slice, &spec)?;
-        let pixel_slice = unsafe { buf_slice.get_unchecked_mut(HEADER_SIZE..) };
-        encode_data(data_slice, pixel_slice, &spec, ColorType::Rgb565)?;
-
-        // 書き込み
-        writer.write_all(&buf_slice)?;
-        writer.flush()?;
+        // 行単位でエンコードしながら書き込み
+        let mut encoder = LimgEncoder::new(writer, spec)?;
+        encoder.write_rows(&self.pixels)?;
+        encoder.finish()?;
 
         Ok(())
     }
@@ -612,7 +858,7 @@ pub struct ImageIndex {
 }
 
 impl ImageIndex {
-    fn new(width: u16, height: u16) -> ImageIndex {
+    pub(crate) fn new(width: u16, height: u16) -> ImageIndex {
         ImageIndex { width, height, x: 0, y: 0 }
     }
 }
@@ -0,0 +1,329 @@
+use core::ops::{Index, IndexMut};
+
+use limg_core::{encode_header, ImageSpec, PixelEndian, HEADER_SIZE, PIXEL_BYTES};
+
+use crate::pixel::Pixel;
+use crate::image::ImageIndex;
+use crate::image_ref::ImageRef;
+use crate::compress;
+use crate::decoder::DecodeOptions;
+use crate::error::{Error, Result};
+
+#[inline(always)]
+const fn image_index(x: u16, y: u16, width: u16) -> usize {
+    y as usize * width as usize + x as usize
+}
+
+/// 呼び出し側が用意した領域にピクセルを保持するLimg画像です。
+/// 
+/// ピクセルは`S`に格納されるため、ヒープを使用しません。
+/// 静的な配列`[Pixel; N]`やフレームバッファの`&mut [Pixel]`を領域として使用でき、アロケーターのない環境でも画像の読み書きや描画ができます。
+/// 
+/// 領域は`width * height`以上の長さが必要で、先頭から`width * height`個のピクセルが使用されます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{ImageBuf, Pixel, Result};
+/// # fn main() -> Result<()> {
+/// let mut framebuffer = [Pixel::BLACK; 32 * 24];
+/// 
+/// let mut image = ImageBuf::new(32, 24, &mut framebuffer[..])?;
+/// image.fill(Pixel::BLUE);
+/// image[(10, 10)] = Pixel::WHITE;
+/// 
+/// let mut buf = [0u8; 12 + 32 * 24 * 2];
+/// image.to_buffer(&mut buf)?;
+/// 
+/// assert_eq!(framebuffer[10 * 32 + 10], Pixel::WHITE);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageBuf<S> {
+    /// 画像の幅
+    width: u16,
+
+    /// 画像の高さ
+    height: u16,
+
+    /// 透明色
+    /// 
+    /// 指定しない場合`None`
+    transparent_color: Option<Pixel>,
+
+    /// ピクセルを格納する領域
+    storage: S,
+}
+
+impl<S: AsRef<[Pixel]>> ImageBuf<S> {
+    /// `width`と`height`、領域を指定して透明色なしのLimg画像を作成します。
+    /// 
+    /// 領域のピクセルはそのまま使用されます。
+    /// 
+    /// # Errors
+    /// 
+    /// `storage`の長さが`width * height`より短い場合、`Error::SizeMismatch`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{ImageBuf, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let image = ImageBuf::new(4, 2, [Pixel::WHITE; 8])?;
+    /// 
+    /// assert_eq!(image[(3, 1)], Pixel::WHITE);
+    /// assert!(ImageBuf::new(4, 4, [Pixel::WHITE; 8]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn new(width: u16, height: u16, storage: S) -> Result<ImageBuf<S>> {
+        if storage.as_ref().len() < width as usize * height as usize {
+            return Err(Error::SizeMismatch);
+        }
+
+        Ok(ImageBuf { width, height, transparent_color: None, storage })
+    }
+
+    /// `width`と`height`、`transparent_color`、領域を指定してLimg画像を作成します。
+    /// 
+    /// # Errors
+    /// 
+    /// `storage`の長さが`width * height`より短い場合、`Error::SizeMismatch`を返します。
+    #[inline]
+    pub fn with_transparent_color(width: u16, height: u16, transparent_color: Pixel, storage: S) -> Result<ImageBuf<S>> {
+        let mut image = ImageBuf::new(width, height, storage)?;
+        image.transparent_color = Some(transparent_color);
+        Ok(image)
+    }
+
+    /// 画像の幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// 画像の高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// 画像の左上隅から始める`ImageIndex`を返します。
+    #[inline(always)]
+    pub fn coordinates(&self) -> ImageIndex {
+        ImageIndex::new(self.width, self.height)
+    }
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    #[inline(always)]
+    pub fn transparent_color(&self) -> Option<Pixel> {
+        self.transparent_color
+    }
+
+    /// 画像の透明色を設定します。
+    /// 
+    /// 指定しない場合`None`を設定してください。
+    #[inline(always)]
+    pub fn set_transparent_color(&mut self, transparent_color: Option<Pixel>) {
+        self.transparent_color = transparent_color;
+    }
+
+    /// `(x, y)`の位置のピクセルの参照を取得します。
+    /// 
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    #[inline(always)]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<&Pixel> {
+        if x < self.width && y < self.height {
+            Some(&self.pixels()[image_index(x, y, self.width)])
+        } else {
+            None
+        }
+    }
+
+    /// 画像のピクセルデータのスライスを取得します。
+    /// 
+    /// 長さは`width * height`で、領域の残りは含まれません。
+    #[inline(always)]
+    pub fn pixels(&self) -> &[Pixel] {
+        &self.storage.as_ref()[..self.width as usize * self.height as usize]
+    }
+
+    /// 領域の参照を返します。
+    #[inline(always)]
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// 画像を破棄して領域を返します。
+    #[inline(always)]
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+
+    /// 画像をエンコードし`buf`に書き込みます。
+    /// 
+    /// ピクセルはリトルエンディアンで書き込まれます。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正か`buf`の長さが足りない場合、`Error`を返します。
+    #[inline(always)]
+    pub fn to_buffer(&self, buf: &mut impl AsMut<[u8]>) -> Result<()> {
+        self.to_buffer_with_endian(buf, PixelEndian::Little)
+    }
+
+    /// 画像を指定された`endian`でピクセルエンコードし`buf`に書き込みます。
+    /// 
+    /// アロケーションは行いません。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正か`buf`の長さが足りない場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{ImageBuf, Pixel, PixelEndian, Result};
+    /// # fn main() -> Result<()> {
+    /// let image = ImageBuf::new(2, 1, [Pixel::RED, Pixel::BLUE])?;
+    /// let mut buf = [0u8; 16];
+    /// image.to_buffer_with_endian(&mut buf, PixelEndian::Big)?;
+    /// 
+    /// assert_eq!(&buf[12..], &[0xF8, 0x00, 0x00, 0x1F]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_buffer_with_endian(&self, buf: &mut impl AsMut<[u8]>, endian: PixelEndian) -> Result<()> {
+        let buf = buf.as_mut();
+
+        let spec = ImageSpec {
+            width: self.width,
+            height: self.height,
+            transparent_color: self.transparent_color.map(|p| p.0),
+            pixel_endian: endian
+        };
+
+        encode_header(buf, &spec)?;
+        let data = buf.get_mut(HEADER_SIZE..HEADER_SIZE + spec.num_pixels() * PIXEL_BYTES).ok_or(Error::OutputBufferTooSmall)?;
+        for (bytes, pixel) in data.chunks_exact_mut(PIXEL_BYTES).zip(self.pixels()) {
+            bytes.copy_from_slice(&pixel.to_bytes(endian));
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: AsRef<[Pixel]> + AsMut<[Pixel]>> ImageBuf<S> {
+    /// `buf`から画像を読み取り、ピクセルを`storage`にデコードします。
+    /// 
+    /// 圧縮された画像も読み取れます。アロケーションは行いません。
+    /// `storage`が画像より長い場合、残りのピクセルは変更されません。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正な場合、`Error`を返します。
+    /// `storage`の長さが画像のピクセル数より短い場合、`Error::OutputBufferTooSmall`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, ImageBuf, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// # let mut image = Image::new(4, 2);
+    /// # image[(3, 1)] = Pixel::RED;
+    /// # let mut buf = [0u8; 28];
+    /// # image.to_buffer(&mut buf)?;
+    /// // let buf = include_bytes!("image.limg");
+    /// let image = ImageBuf::from_buffer(&buf, [Pixel::BLACK; 64])?;
+    /// 
+    /// assert_eq!((image.width(), image.height()), (4, 2));
+    /// assert_eq!(image[(3, 1)], Pixel::RED);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_buffer(buf: impl AsRef<[u8]>, mut storage: S) -> Result<ImageBuf<S>> {
+        let buf = buf.as_ref();
+        if compress::is_compressed(buf) {
+            let header = compress::decode_into(buf, storage.as_mut(), &DecodeOptions::default())?;
+            return Ok(ImageBuf {
+                width: header.spec.width,
+                height: header.spec.height,
+                transparent_color: header.spec.transparent_color.map(Pixel),
+                storage,
+            });
+        }
+
+        let image = ImageRef::new(buf)?;
+        ImageBuf::from_image_ref(&image, storage)
+    }
+
+    /// `image`のピクセルを`storage`にデコードします。
+    /// 
+    /// # Errors
+    /// 
+    /// `storage`の長さが画像のピクセル数より短い場合、`Error::OutputBufferTooSmall`を返します。
+    pub fn from_image_ref(image: &ImageRef<'_>, mut storage: S) -> Result<ImageBuf<S>> {
+        let pixels = storage.as_mut().get_mut(..image.width() as usize * image.height() as usize).ok_or(Error::OutputBufferTooSmall)?;
+        for (dst, src) in pixels.iter_mut().zip(image.pixels()) {
+            *dst = src;
+        }
+
+        Ok(ImageBuf {
+            width: image.width(),
+            height: image.height(),
+            transparent_color: image.transparent_color(),
+            storage,
+        })
+    }
+
+    /// `(x, y)`の位置のピクセルの可変参照を取得します。
+    /// 
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    #[inline(always)]
+    pub fn get_pixel_mut(&mut self, x: u16, y: u16) -> Option<&mut Pixel> {
+        if x < self.width && y < self.height {
+            let index = image_index(x, y, self.width);
+            Some(&mut self.pixels_mut()[index])
+        } else {
+            None
+        }
+    }
+
+    /// 画像のピクセルデータの可変スライスを取得します。
+    /// 
+    /// 長さは`width * height`で、領域の残りは含まれません。
+    #[inline(always)]
+    pub fn pixels_mut(&mut self) -> &mut [Pixel] {
+        let len = self.width as usize * self.height as usize;
+        &mut self.storage.as_mut()[..len]
+    }
+
+    /// 指定した色で画像を塗りつぶします。
+    #[inline(always)]
+    pub fn fill(&mut self, pixel: Pixel) {
+        self.pixels_mut().fill(pixel);
+    }
+}
+
+impl<S: AsRef<[Pixel]>> Index<(u16, u16)> for ImageBuf<S> {
+    type Output = Pixel;
+
+    #[inline(always)]
+    fn index(&self, index: (u16, u16)) -> &Self::Output {
+        assert!(index.0 < self.width && index.1 < self.height);
+        &self.pixels()[image_index(index.0, index.1, self.width)]
+    }
+}
+
+impl<S: AsRef<[Pixel]> + AsMut<[Pixel]>> IndexMut<(u16, u16)> for ImageBuf<S> {
+    #[inline(always)]
+    fn index_mut(&mut self, index: (u16, u16)) -> &mut Self::Output {
+        assert!(index.0 < self.width && index.1 < self.height);
+        let index = image_index(index.0, index.1, self.width);
+        &mut self.pixels_mut()[index]
+    }
+}
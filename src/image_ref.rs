@@ -0,0 +1,333 @@
+use core::iter::FusedIterator;
+use core::slice::ChunksExact;
+
+use limg_core::{ImageSpec, PixelEndian, HEADER_SIZE, PIXEL_BYTES};
+
+use crate::image::Image;
+use crate::pixel::Pixel;
+use crate::header::{self, HeaderError};
+use crate::error::{Error, HeaderField, Result};
+
+/// Limg画像を`ImageRef`としてバイナリに埋め込むマクロです。
+/// 
+/// パスは[`include_bytes!`]と同じく、呼び出したファイルからの相対パスで指定します。
+/// ヘッダーの検証はコンパイル時に行われるため、壊れた画像はビルドエラーになります。
+/// 
+/// # Examples
+/// 
+/// ```
+/// use limg::{include_limg, ImageRef, Pixel};
+/// 
+/// // static LOGO: ImageRef = include_limg!("logo.limg");
+/// static LOGO: ImageRef = include_limg!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/limg/red_le.limg"));
+/// const WIDTH: u16 = LOGO.width();
+/// 
+/// assert_eq!(WIDTH, 300);
+/// assert_eq!(LOGO.get_pixel(0, 0), Some(Pixel::RED));
+/// ```
+#[macro_export]
+macro_rules! include_limg {
+    ($path:expr) => {{
+        const IMAGE: $crate::ImageRef<'static> = $crate::ImageRef::from_bytes(include_bytes!($path));
+        IMAGE
+    }};
+}
+
+/// エンコードされたLimg画像のバッファをコピーせずに参照する画像です。
+/// 
+/// ピクセルはアクセスのたびにバッファからデコードされるため、ヒープを使用しません。
+/// [`include_limg!`]で埋め込んだ画像をフラッシュメモリから直接読み取る用途を想定しています。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, ImageRef, Pixel, Result};
+/// # fn main() -> Result<()> {
+/// # let mut image = Image::new(4, 2);
+/// # image[(3, 1)] = Pixel::RED;
+/// # let mut buf = [0u8; 28];
+/// # image.to_buffer(&mut buf)?;
+/// // let buf = include_bytes!("image.limg");
+/// let image = ImageRef::new(&buf)?;
+/// 
+/// assert_eq!((image.width(), image.height()), (4, 2));
+/// assert_eq!(image.get_pixel(3, 1), Some(Pixel::RED));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageRef<'a> {
+    /// ヘッダーを除いたピクセルデータ
+    data: &'a [u8],
+
+    /// 画像の幅
+    width: u16,
+
+    /// 画像の高さ
+    height: u16,
+
+    /// 透明色
+    transparent_color: Option<Pixel>,
+
+    /// ピクセルデータのエンディアン
+    endian: PixelEndian,
+}
+
+impl<'a> ImageRef<'a> {
+    /// `buf`のヘッダーを検証し、`ImageRef`を作成します。
+    /// 
+    /// `buf`はピクセルデータの末尾まで含んでいる必要があります。それ以降のデータは無視されます。
+    /// 
+    /// # Errors
+    /// 
+    /// ヘッダーが不正か`buf`の長さが足りない場合、`Error`を返します。
+    pub fn new(buf: &'a [u8]) -> Result<ImageRef<'a>> {
+        let spec = header::check_header(buf)?;
+
+        let end = HEADER_SIZE + spec.num_pixels() * PIXEL_BYTES;
+        let data = buf.get(HEADER_SIZE..end).ok_or(Error::Truncated { expected: end, actual: buf.len() })?;
+
+        Ok(ImageRef {
+            data,
+            width: spec.width,
+            height: spec.height,
+            transparent_color: spec.transparent_color.map(Pixel),
+            endian: spec.pixel_endian,
+        })
+    }
+
+    /// `buf`のヘッダーをコンパイル時に検証し、`ImageRef`を作成します。
+    /// 
+    /// `const`や`static`の初期化に使用できます。通常は[`include_limg!`]を使用してください。
+    /// 
+    /// # Panics
+    /// 
+    /// ヘッダーが不正か`buf`の長さが足りない場合パニックします。
+    /// 定数式で呼び出した場合はビルドエラーになります。
+    /// 
+    /// ```compile_fail
+    /// # use limg::ImageRef;
+    /// // ピクセルデータが足りない
+    /// const IMAGE: ImageRef = ImageRef::from_bytes(b"LIMG\x01\x00\x01\x00\x01\x00\x00\x00");
+    /// ```
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{ImageRef, Pixel};
+    /// const IMAGE: ImageRef = ImageRef::from_bytes(b"LIMG\x01\x03\x02\x00\x01\x00\x1F\x00\x1F\x00\x00\xF8");
+    /// 
+    /// assert_eq!((IMAGE.width(), IMAGE.height()), (2, 1));
+    /// assert_eq!(IMAGE.transparent_color(), Some(Pixel::BLUE));
+    /// assert_eq!(IMAGE.get_pixel(1, 0), Some(Pixel::RED));
+    /// ```
+    pub const fn from_bytes(buf: &'a [u8]) -> ImageRef<'a> {
+        let spec = match header::parse_header(buf) {
+            Ok(spec) => spec,
+            Err(HeaderError::Truncated) => panic!("Limg画像のヘッダーが不完全です"),
+            Err(HeaderError::Invalid(HeaderField::Signature)) => panic!("Limg画像のシグネチャが不正です"),
+            Err(HeaderError::Invalid(HeaderField::Version)) => panic!("Limg画像のバージョンに対応していません"),
+            Err(HeaderError::Invalid(HeaderField::Flags)) => panic!("Limg画像のフラグが不正です"),
+            Err(HeaderError::Invalid(HeaderField::Width | HeaderField::Height)) => panic!("Limg画像の幅および高さが0です"),
+        };
+        let (width, height) = (spec.width, spec.height);
+        let transparent_color = match spec.transparent_color {
+            Some(color) => Some(Pixel(color)),
+            None => None,
+        };
+
+        let len = width as usize * height as usize * PIXEL_BYTES;
+        let (_, data) = buf.split_at(HEADER_SIZE);
+        if data.len() < len {
+            panic!("Limg画像のピクセルデータが不足しています");
+        }
+        let (data, _) = data.split_at(len);
+
+        ImageRef { data, width, height, transparent_color, endian: spec.pixel_endian }
+    }
+
+    /// 画像の情報を返します。
+    #[inline(always)]
+    pub const fn spec(&self) -> ImageSpec {
+        ImageSpec {
+            width: self.width,
+            height: self.height,
+            transparent_color: match self.transparent_color {
+                Some(pixel) => Some(pixel.0),
+                None => None,
+            },
+            pixel_endian: self.endian,
+        }
+    }
+
+    /// 画像の幅を返します。
+    #[inline(always)]
+    pub const fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// 画像の高さを返します。
+    #[inline(always)]
+    pub const fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    #[inline(always)]
+    pub const fn transparent_color(&self) -> Option<Pixel> {
+        self.transparent_color
+    }
+
+    /// ピクセルデータのエンディアンを返します。
+    #[inline(always)]
+    pub const fn pixel_endian(&self) -> PixelEndian {
+        self.endian
+    }
+
+    /// ヘッダーを除いたエンコード済みのピクセルデータを返します。
+    #[inline(always)]
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// `(x, y)`の位置のピクセルを取得します。
+    /// 
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, ImageRef, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut image = Image::new(2, 2);
+    /// image[(1, 0)] = Pixel::GREEN;
+    /// let mut buf = [0u8; 20];
+    /// image.to_buffer(&mut buf)?;
+    /// 
+    /// let image = ImageRef::new(&buf)?;
+    /// 
+    /// assert_eq!(image.get_pixel(1, 0), Some(Pixel::GREEN));
+    /// assert_eq!(image.get_pixel(2, 0), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub const fn get_pixel(&self, x: u16, y: u16) -> Option<Pixel> {
+        if x < self.width && y < self.height {
+            let index = (y as usize * self.width as usize + x as usize) * PIXEL_BYTES;
+            Some(Pixel::from_bytes([self.data[index], self.data[index + 1]], self.endian))
+        } else {
+            None
+        }
+    }
+
+    /// 左上から行優先にピクセルを返す`Iterator`を返します。
+    #[inline(always)]
+    pub fn pixels(&self) -> Pixels<'a> {
+        Pixels::new(self.data, self.endian)
+    }
+
+    /// `y`行目のピクセルを返す`Iterator`を返します。
+    /// 
+    /// # Panics
+    /// 
+    /// `y`が高さ以上の場合パニックします。
+    #[inline(always)]
+    pub fn row(&self, y: u16) -> Pixels<'a> {
+        assert!(y < self.height());
+        let stride = self.width() as usize * PIXEL_BYTES;
+        let start = y as usize * stride;
+        Pixels::new(&self.data[start..start + stride], self.endian)
+    }
+
+    /// 上の行から順に各行のピクセルの`Iterator`を返す`Iterator`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, ImageRef, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut image = Image::new(3, 2);
+    /// image[(2, 1)] = Pixel::BLUE;
+    /// let mut buf = [0u8; 24];
+    /// image.to_buffer(&mut buf)?;
+    /// 
+    /// let image = ImageRef::new(&buf)?;
+    /// let last = image.rows().last().unwrap();
+    /// 
+    /// assert_eq!(last.last(), Some(Pixel::BLUE));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = Pixels<'a>> + DoubleEndedIterator + use<'a> {
+        let endian = self.endian;
+        self.data
+            .chunks_exact(self.width() as usize * PIXEL_BYTES)
+            .map(move |row| Pixels::new(row, endian))
+    }
+
+    /// ピクセルをデコードして新しい`Image`を作成します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, ImageRef, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut image = Image::with_transparent_color(2, 2, Pixel::MAGENTA);
+    /// image[(0, 1)] = Pixel::MAGENTA;
+    /// let mut buf = [0u8; 20];
+    /// image.to_buffer(&mut buf)?;
+    /// 
+    /// assert_eq!(ImageRef::new(&buf)?.to_image(), image);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_image(&self) -> Image {
+        Image::from_parts(self.width(), self.height(), self.transparent_color(), self.pixels().collect())
+    }
+}
+
+/// [`ImageRef`]のピクセルをデコードしながら返す`Iterator`です。
+#[derive(Clone, Debug)]
+pub struct Pixels<'a> {
+    chunks: ChunksExact<'a, u8>,
+    endian: PixelEndian,
+}
+
+impl<'a> Pixels<'a> {
+    #[inline(always)]
+    fn new(data: &'a [u8], endian: PixelEndian) -> Pixels<'a> {
+        Pixels { chunks: data.chunks_exact(PIXEL_BYTES), endian }
+    }
+}
+
+impl Iterator for Pixels<'_> {
+    type Item = Pixel;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Pixel> {
+        self.chunks.next().map(|b| Pixel::from_bytes([b[0], b[1]], self.endian))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+
+    #[inline(always)]
+    fn nth(&mut self, n: usize) -> Option<Pixel> {
+        self.chunks.nth(n).map(|b| Pixel::from_bytes([b[0], b[1]], self.endian))
+    }
+}
+
+impl DoubleEndedIterator for Pixels<'_> {
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Pixel> {
+        self.chunks.next_back().map(|b| Pixel::from_bytes([b[0], b[1]], self.endian))
+    }
+}
+
+impl ExactSizeIterator for Pixels<'_> {}
+impl FusedIterator for Pixels<'_> {}
@@ -0,0 +1,560 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::image::Image;
+use crate::pixel::Pixel;
+use crate::error::{Error, Result};
+
+/// パレット画像のシグネチャ
+const SIGNATURE: [u8; 4] = *b"LIMI";
+
+/// 対応しているパレット画像のバージョン
+const VERSION: u8 = 1;
+
+/// パレットの0番が透明色であることを示すフラグ
+const FLAG_TRANSPARENT: u8 = 0b10;
+
+/// パレット画像のヘッダーのサイズ
+/// 
+/// シグネチャ(4)、バージョン(1)、フラグ(1)、幅(2)、高さ(2)、ビット深度(1)、パレット数 - 1(1)の順に並びます。
+/// その後にリトルエンディアンのパレットと、行ごとにバイト境界に揃えたインデックスが続きます。
+const INDEXED_HEADER_SIZE: usize = 12;
+
+/// パレットの最大色数
+const MAX_COLORS: usize = 256;
+
+/// パレット画像のインデックスのビット数です。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BitDepth {
+    /// 2色
+    One,
+
+    /// 4色
+    Two,
+
+    /// 16色
+    Four,
+
+    /// 256色
+    Eight,
+}
+
+impl BitDepth {
+    /// 1ピクセルあたりのビット数を返します。
+    #[inline(always)]
+    pub const fn bits(self) -> u8 {
+        match self {
+            BitDepth::One => 1,
+            BitDepth::Two => 2,
+            BitDepth::Four => 4,
+            BitDepth::Eight => 8,
+        }
+    }
+
+    /// 表現できる色数を返します。
+    #[inline(always)]
+    pub const fn max_colors(self) -> usize {
+        1 << self.bits()
+    }
+
+    /// `colors`色を表現できる最小のビット数を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::BitDepth;
+    /// assert_eq!(BitDepth::for_colors(2), Some(BitDepth::One));
+    /// assert_eq!(BitDepth::for_colors(5), Some(BitDepth::Four));
+    /// assert_eq!(BitDepth::for_colors(257), None);
+    /// ```
+    pub const fn for_colors(colors: usize) -> Option<BitDepth> {
+        match colors {
+            0..=2 => Some(BitDepth::One),
+            3..=4 => Some(BitDepth::Two),
+            5..=16 => Some(BitDepth::Four),
+            17..=MAX_COLORS => Some(BitDepth::Eight),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    fn from_bits(bits: u8) -> Option<BitDepth> {
+        match bits {
+            1 => Some(BitDepth::One),
+            2 => Some(BitDepth::Two),
+            4 => Some(BitDepth::Four),
+            8 => Some(BitDepth::Eight),
+            _ => None,
+        }
+    }
+
+    /// `width`ピクセルの行のバイト数を返します。
+    #[inline(always)]
+    fn row_bytes(self, width: u16) -> usize {
+        (width as usize * self.bits() as usize).div_ceil(8)
+    }
+}
+
+/// RGB565のパレットとインデックスで表すLimg画像です。
+/// 
+/// インデックスのビット数はパレットの色数から決まり、エンコード時は1、2、4、8ビットに詰めて書き込まれます。
+/// 透明色がある場合はパレットの0番が透明色になります。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{BitDepth, IndexedImage, Pixel, Result};
+/// # fn main() -> Result<()> {
+/// let mut icon = IndexedImage::new(16, 16, vec![Pixel::BLACK, Pixel::WHITE])?;
+/// icon.set_index(8, 8, 1);
+/// 
+/// assert_eq!(icon.bit_depth(), BitDepth::One);
+/// assert_eq!(icon.to_image()[(8, 8)], Pixel::WHITE);
+/// 
+/// let mut buf = vec![0u8; icon.encoded_size()];
+/// icon.to_buffer(&mut buf)?;
+/// assert_eq!(buf.len(), 12 + 2 * 2 + 16 * 2);
+/// assert_eq!(IndexedImage::from_buffer(&buf)?, icon);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedImage {
+    /// 画像の幅
+    width: u16,
+
+    /// 画像の高さ
+    height: u16,
+
+    /// パレット
+    palette: Vec<Pixel>,
+
+    /// パレットの0番が透明色かどうか
+    transparent: bool,
+
+    /// ピクセルごとのパレットのインデックス
+    indices: Box<[u8]>,
+}
+
+impl IndexedImage {
+    /// `width`と`height`、`palette`を指定して透明色なしのパレット画像を作成します。
+    /// 
+    /// すべてのピクセルはインデックス`0`で初期化されます。
+    /// 
+    /// # Errors
+    /// 
+    /// `palette`が空か256色を超える場合、`Error::SizeMismatch`を返します。
+    pub fn new(width: u16, height: u16, palette: Vec<Pixel>) -> Result<IndexedImage> {
+        IndexedImage::from_indices(width, height, palette, false, vec![0; width as usize * height as usize])
+    }
+
+    /// パレット0番の`transparent_color`を透明色とするパレット画像を作成します。
+    /// 
+    /// `palette`は0番以降の色で、先頭に`transparent_color`が追加されます。
+    /// すべてのピクセルは透明色で初期化されます。
+    /// 
+    /// # Errors
+    /// 
+    /// 透明色を含めて256色を超える場合、`Error::SizeMismatch`を返します。
+    pub fn with_transparent_color(width: u16, height: u16, transparent_color: Pixel, palette: &[Pixel]) -> Result<IndexedImage> {
+        let mut colors = Vec::with_capacity(palette.len() + 1);
+        colors.push(transparent_color);
+        colors.extend_from_slice(palette);
+        IndexedImage::from_indices(width, height, colors, true, vec![0; width as usize * height as usize])
+    }
+
+    /// 各フィールドを指定してパレット画像を作成します。
+    /// 
+    /// `indices`は1ピクセル1バイトで、行優先で`width * height`個並んでいる必要があります。
+    /// `transparent`が`true`の場合、パレットの0番が透明色になります。
+    /// 
+    /// # Errors
+    /// 
+    /// `palette`が空か256色を超える場合、`indices`の長さが画像サイズと異なるか、
+    /// パレットの範囲外のインデックスがある場合、`Error::SizeMismatch`を返します。
+    pub fn from_indices(width: u16, height: u16, palette: Vec<Pixel>, transparent: bool, indices: Vec<u8>) -> Result<IndexedImage> {
+        if palette.is_empty() || palette.len() > MAX_COLORS || indices.len() != width as usize * height as usize {
+            return Err(Error::SizeMismatch);
+        }
+        if indices.iter().any(|&i| i as usize >= palette.len()) {
+            return Err(Error::SizeMismatch);
+        }
+
+        Ok(IndexedImage { width, height, palette, transparent, indices: indices.into_boxed_slice() })
+    }
+
+    /// 画像の幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// 画像の高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// パレットを返します。
+    #[inline(always)]
+    pub fn palette(&self) -> &[Pixel] {
+        &self.palette
+    }
+
+    /// パレットの可変スライスを返します。
+    /// 
+    /// 色は変更できますが、色数は変更できません。
+    #[inline(always)]
+    pub fn palette_mut(&mut self) -> &mut [Pixel] {
+        &mut self.palette
+    }
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 透明色がある場合はパレットの0番の色です。指定がない場合`None`になります。
+    #[inline(always)]
+    pub fn transparent_color(&self) -> Option<Pixel> {
+        if self.transparent { Some(self.palette[0]) } else { None }
+    }
+
+    /// パレットの色数から決まるインデックスのビット数を返します。
+    #[inline(always)]
+    pub fn bit_depth(&self) -> BitDepth {
+        // パレットは1色以上256色以下
+        BitDepth::for_colors(self.palette.len()).unwrap_or(BitDepth::Eight)
+    }
+
+    /// 行優先で並んだインデックスを返します。
+    #[inline(always)]
+    pub fn indices(&self) -> &[u8] {
+        &self.indices
+    }
+
+    /// `(x, y)`の位置のインデックスを返します。
+    /// 
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    #[inline(always)]
+    pub fn get_index(&self, x: u16, y: u16) -> Option<u8> {
+        if x < self.width && y < self.height {
+            Some(self.indices[y as usize * self.width as usize + x as usize])
+        } else {
+            None
+        }
+    }
+
+    /// `(x, y)`の位置のインデックスを設定します。
+    /// 
+    /// # Panics
+    /// 
+    /// `(x, y)`が範囲外か、`index`がパレットの範囲外の場合パニックします。
+    #[inline(always)]
+    pub fn set_index(&mut self, x: u16, y: u16, index: u8) {
+        assert!(x < self.width && y < self.height);
+        assert!((index as usize) < self.palette.len());
+        self.indices[y as usize * self.width as usize + x as usize] = index;
+    }
+
+    /// `(x, y)`の位置のピクセルを返します。
+    /// 
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    #[inline(always)]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<Pixel> {
+        self.get_index(x, y).map(|i| self.palette[i as usize])
+    }
+
+    /// パレットを展開した`Image`を返します。
+    pub fn to_image(&self) -> Image {
+        let pixels = self.indices.iter().map(|&i| self.palette[i as usize]).collect();
+        Image::from_parts(self.width, self.height, self.transparent_color(), pixels)
+    }
+
+    /// エンコードした場合のバイト数を返します。
+    pub fn encoded_size(&self) -> usize {
+        INDEXED_HEADER_SIZE + self.palette.len() * 2 + self.bit_depth().row_bytes(self.width) * self.height as usize
+    }
+
+    /// パレット画像をエンコードし`buf`に書き込みます。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像サイズが0か`buf`の長さが[`encoded_size`]より短い場合、`Error`を返します。
+    /// 
+    /// [`encoded_size`]: IndexedImage::encoded_size
+    pub fn to_buffer(&self, buf: &mut impl AsMut<[u8]>) -> Result<()> {
+        let buf = buf.as_mut();
+        if self.width == 0 || self.height == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+        if buf.len() < self.encoded_size() {
+            return Err(Error::OutputBufferTooSmall);
+        }
+
+        let depth = self.bit_depth();
+        buf[0..4].copy_from_slice(&SIGNATURE);
+        buf[4] = VERSION;
+        buf[5] = if self.transparent { FLAG_TRANSPARENT } else { 0 };
+        buf[6..8].copy_from_slice(&self.width.to_le_bytes());
+        buf[8..10].copy_from_slice(&self.height.to_le_bytes());
+        buf[10] = depth.bits();
+        buf[11] = (self.palette.len() - 1) as u8;
+
+        let (palette, data) = buf[INDEXED_HEADER_SIZE..].split_at_mut(self.palette.len() * 2);
+        for (bytes, color) in palette.chunks_exact_mut(2).zip(&self.palette) {
+            bytes.copy_from_slice(&color.0.to_le_bytes());
+        }
+
+        // 上位ビットから詰める
+        let bits = depth.bits() as usize;
+        let row_bytes = depth.row_bytes(self.width);
+        for (dst, row) in data.chunks_exact_mut(row_bytes).zip(self.indices.chunks_exact(self.width as usize)) {
+            dst.fill(0);
+            for (x, &index) in row.iter().enumerate() {
+                let bit = x * bits;
+                dst[bit / 8] |= index << (8 - bits - bit % 8);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `buf`からパレット画像を読み取ります。
+    /// 
+    /// # Errors
+    /// 
+    /// データが不正な場合、`Error`を返します。
+    pub fn from_buffer(buf: impl AsRef<[u8]>) -> Result<IndexedImage> {
+        let buf = buf.as_ref();
+        if buf.len() < INDEXED_HEADER_SIZE {
+            return Err(Error::InputBufferTooSmall);
+        }
+        if buf[0..4] != SIGNATURE || buf[4] != VERSION || buf[5] & !FLAG_TRANSPARENT != 0 {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        let width = u16::from_le_bytes([buf[6], buf[7]]);
+        let height = u16::from_le_bytes([buf[8], buf[9]]);
+        if width == 0 || height == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+        let depth = BitDepth::from_bits(buf[10]).ok_or(Error::UnsupportedFormat)?;
+        let colors = buf[11] as usize + 1;
+        if BitDepth::for_colors(colors) != Some(depth) {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        let palette_end = INDEXED_HEADER_SIZE + colors * 2;
+        let palette = buf.get(INDEXED_HEADER_SIZE..palette_end).ok_or(Error::InputBufferTooSmall)?;
+        let palette = palette.chunks_exact(2).map(|b| Pixel(u16::from_le_bytes([b[0], b[1]]))).collect();
+
+        let row_bytes = depth.row_bytes(width);
+        let data = buf.get(palette_end..palette_end + row_bytes * height as usize).ok_or(Error::InputBufferTooSmall)?;
+
+        let bits = depth.bits() as usize;
+        let mask = (depth.max_colors() - 1) as u8;
+        let mut indices = Vec::with_capacity(width as usize * height as usize);
+        for row in data.chunks_exact(row_bytes) {
+            indices.extend((0..width as usize).map(|x| {
+                let bit = x * bits;
+                (row[bit / 8] >> (8 - bits - bit % 8)) & mask
+            }));
+        }
+
+        IndexedImage::from_indices(width, height, palette, buf[5] & FLAG_TRANSPARENT != 0, indices)
+            .map_err(|_| Error::UnsupportedFormat)
+    }
+}
+
+#[cfg(feature = "std")]
+impl IndexedImage {
+    /// `path`からパレット画像を読み取ります。
+    /// 
+    /// # Errors
+    /// 
+    /// データが不正かIO操作に失敗した場合、`Error`を返します。
+    #[inline(always)]
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<IndexedImage> {
+        IndexedImage::from_buffer(std::fs::read(path)?)
+    }
+
+    /// パレット画像をエンコードし`path`に保存します。既にファイルが存在する場合上書きします。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像サイズが0かIO操作に失敗した場合、`Error`を返します。
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let mut buf = vec![0u8; self.encoded_size()];
+        self.to_buffer(&mut buf)?;
+        std::fs::write(path, buf)?;
+        Ok(())
+    }
+}
+
+/// 減色対象の色と出現数
+#[derive(Clone, Copy)]
+struct ColorCount {
+    pixel: Pixel,
+    rgb: [u8; 3],
+    count: u32,
+}
+
+/// メディアンカットで分割する色の集合
+struct ColorBox {
+    start: usize,
+    end: usize,
+}
+
+impl ColorBox {
+    /// 最も範囲の広いチャンネルとその範囲を返します。
+    fn widest_channel(&self, colors: &[ColorCount]) -> (usize, u8) {
+        (0..3).map(|ch| {
+            let values = colors[self.start..self.end].iter().map(|c| c.rgb[ch]);
+            let (min, max) = values.fold((u8::MAX, u8::MIN), |(min, max), v| (min.min(v), max.max(v)));
+            (ch, max - min)
+        }).max_by_key(|&(_, range)| range).unwrap_or((0, 0))
+    }
+
+    /// 出現数で重み付けした平均色を返します。
+    fn average(&self, colors: &[ColorCount]) -> Pixel {
+        let mut sum = [0u64; 3];
+        let mut total = 0u64;
+        for c in &colors[self.start..self.end] {
+            for (s, &v) in sum.iter_mut().zip(&c.rgb) {
+                *s += v as u64 * c.count as u64;
+            }
+            total += c.count as u64;
+        }
+
+        Pixel::from_rgb(sum.map(|s| ((s + total / 2) / total) as u8))
+    }
+}
+
+/// 2色間の距離の2乗を返します。
+#[inline(always)]
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    (0..3).map(|ch| {
+        let d = a[ch] as i32 - b[ch] as i32;
+        (d * d) as u32
+    }).sum()
+}
+
+/// `colors`をメディアンカットで`max_colors`色以下のパレットに減色します。
+fn median_cut(colors: &mut [ColorCount], max_colors: usize, key: Option<Pixel>) -> Vec<Pixel> {
+    let mut boxes = vec![ColorBox { start: 0, end: colors.len() }];
+
+    while boxes.len() < max_colors {
+        // 分割できる箱のうち、最も範囲の広い箱を選ぶ
+        let Some((index, channel)) = boxes.iter().enumerate()
+            .filter(|(_, b)| b.end - b.start >= 2)
+            .map(|(i, b)| (i, b.widest_channel(colors)))
+            .filter(|&(_, (_, range))| range > 0)
+            .max_by_key(|&(_, (_, range))| range)
+            .map(|(i, (channel, _))| (i, channel))
+        else {
+            break;
+        };
+
+        let ColorBox { start, end } = boxes[index];
+        let slice = &mut colors[start..end];
+        slice.sort_unstable_by_key(|c| c.rgb[channel]);
+
+        // 出現数の中央で分割する
+        let half = slice.iter().map(|c| c.count as u64).sum::<u64>() / 2;
+        let mut acc = 0;
+        let mut split = slice.iter().position(|c| {
+            acc += c.count as u64;
+            acc > half
+        }).unwrap_or(0);
+        split = split.clamp(1, slice.len() - 1);
+
+        boxes[index] = ColorBox { start, end: start + split };
+        boxes.push(ColorBox { start: start + split, end });
+    }
+
+    boxes.iter().map(|b| {
+        let color = b.average(colors);
+        if Some(color) != key {
+            return color;
+        }
+
+        // 平均が透明色と一致した場合は箱の中で最も近い色を使う
+        let rgb = color.into_rgb();
+        colors[b.start..b.end].iter()
+            .map(|c| c.pixel)
+            .filter(|&c| Some(c) != key)
+            .min_by_key(|c| distance(c.into_rgb(), rgb))
+            .unwrap_or(color)
+    }).collect()
+}
+
+impl Image {
+    /// 画像を`max_colors`色以下のパレット画像に減色します。
+    /// 
+    /// 使われている色が`max_colors`色以下の場合はそのままパレットになり、それ以外の場合はメディアンカットで減色します。
+    /// 透明色がある場合はパレットの0番に予約され、残りの色数で減色されます。
+    /// `max_colors`は`2`から`256`の範囲に丸められます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{BitDepth, Image, Pixel};
+    /// let mut image = Image::with_transparent_color(8, 8, Pixel::MAGENTA);
+    /// image.fill(Pixel::MAGENTA);
+    /// image[(1, 1)] = Pixel::RED;
+    /// image[(2, 2)] = Pixel::BLUE;
+    /// 
+    /// let indexed = image.quantize(16);
+    /// 
+    /// assert_eq!(indexed.palette()[0], Pixel::MAGENTA);
+    /// assert_eq!(indexed.bit_depth(), BitDepth::Two);
+    /// assert_eq!(indexed.to_image(), image);
+    /// ```
+    pub fn quantize(&self, max_colors: usize) -> IndexedImage {
+        let max_colors = max_colors.clamp(2, MAX_COLORS);
+        let key = self.transparent_color();
+
+        // 出現する色を数える
+        let mut sorted: Vec<u16> = self.pixels().iter().filter(|&&p| Some(p) != key).map(|p| p.0).collect();
+        sorted.sort_unstable();
+        let mut colors: Vec<ColorCount> = Vec::new();
+        for value in sorted {
+            match colors.last_mut() {
+                Some(last) if last.pixel.0 == value => last.count += 1,
+                _ => colors.push(ColorCount { pixel: Pixel(value), rgb: Pixel(value).into_rgb(), count: 1 }),
+            }
+        }
+        let unique: Vec<u16> = colors.iter().map(|c| c.pixel.0).collect();
+
+        let budget = if key.is_some() { max_colors - 1 } else { max_colors };
+        let mut palette: Vec<Pixel> = key.into_iter().collect();
+        if colors.len() <= budget {
+            palette.extend(unique.iter().map(|&v| Pixel(v)));
+        } else {
+            palette.extend(median_cut(&mut colors, budget, key));
+        }
+        if palette.is_empty() {
+            palette.push(Pixel::BLACK);
+        }
+
+        // 色ごとに最も近いパレットを求めておく
+        let offset = key.map_or(0, |_| 1);
+        let nearest: Vec<u8> = unique.iter().map(|&v| {
+            let rgb = Pixel(v).into_rgb();
+            let index = palette[offset..].iter().enumerate()
+                .min_by_key(|(_, p)| distance(p.into_rgb(), rgb))
+                .map_or(0, |(i, _)| i + offset);
+            index as u8
+        }).collect();
+
+        let indices = self.pixels().iter().map(|&p| {
+            if Some(p) == key {
+                0
+            } else {
+                // 透明色以外の色はすべて`unique`に含まれる
+                unique.binary_search(&p.0).map_or(0, |i| nearest[i])
+            }
+        }).collect();
+
+        IndexedImage { width: self.width(), height: self.height(), palette, transparent: key.is_some(), indices }
+    }
+}
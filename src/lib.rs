@@ -6,8 +6,56 @@ extern crate alloc;
 mod pixel;
 mod image;
 mod error;
+mod header;
+mod decoder;
+mod encoder;
+#[cfg(feature = "png")]
+mod png;
+mod bmp;
+mod pnm;
+mod dither;
+mod rect;
+mod blit;
+mod generic;
+mod view;
+mod transform;
+mod resize;
+pub mod draw;
+mod font;
+mod image_ref;
+mod image_buf;
+mod animation;
+mod patch;
+mod compress;
+mod indexed;
+mod probe;
+#[cfg(feature = "embedded-graphics")]
+mod embedded_graphics;
+#[cfg(feature = "mmap")]
+mod mmap;
 
-pub use limg_core::PixelEndian;
+pub use limg_core::{ImageSpec, PixelEndian};
 pub use pixel::Pixel;
 pub use image::{Image, ImageIndex};
-pub use error::{Error, Result};
+pub use image_ref::{ImageRef, Pixels};
+pub use image_buf::ImageBuf;
+pub use animation::{Animation, Frame};
+pub use patch::{ImagePatch, PatchRegion};
+pub use compress::Compression;
+pub use indexed::{BitDepth, IndexedImage};
+pub use probe::{probe_buffer, ImageInfo};
+#[cfg(feature = "std")]
+pub use probe::{probe, probe_read};
+#[cfg(feature = "mmap")]
+pub use mmap::MappedImage;
+pub use rect::Rect;
+pub use generic::{GenericImage, GenericImageMut};
+pub use view::{ImageView, ImageViewMut};
+pub use font::{Font, GlyphMetrics};
+pub use pnm::PnmFormat;
+pub use dither::Dither;
+#[cfg(feature = "std")]
+pub use resize::Filter;
+pub use decoder::{LimgDecoder, ByteSource, DecodeOptions};
+pub use encoder::{LimgEncoder, ByteSink};
+pub use error::{Error, HeaderField, Result};
@@ -0,0 +1,228 @@
+use std::sync::OnceLock;
+
+use limg_core::{ImageSpec, PixelEndian, HEADER_SIZE, PIXEL_BYTES};
+use memmap2::Mmap;
+
+use crate::image::Image;
+use crate::pixel::Pixel;
+use crate::generic::GenericImage;
+use crate::header;
+use crate::compress;
+use crate::error::{Error, Result};
+
+/// ピクセルのエンディアンが実行環境と同じかどうかを返します。
+#[inline(always)]
+fn is_native_endian(endian: PixelEndian) -> bool {
+    match endian {
+        PixelEndian::Little => cfg!(target_endian = "little"),
+        PixelEndian::Big => cfg!(target_endian = "big"),
+    }
+}
+
+/// メモリマップしたLimg画像です。
+/// 
+/// [`Image::open_mmap`]で作成します。読み取り専用で、ファイル全体を読み込みません。
+/// 
+/// ピクセルのエンディアンが実行環境と同じ場合、ファイルのピクセルデータを[`pixels`]でコピーせずに参照できます。
+/// 異なる場合は[`GenericImage::row`]で行ごとに初めてアクセスしたときにデコードし、その結果を保持します。
+/// 
+/// [`pixels`]: MappedImage::pixels
+#[derive(Debug)]
+pub struct MappedImage {
+    /// マップしたファイル
+    map: Mmap,
+
+    /// 画像の仕様
+    spec: ImageSpec,
+
+    /// ピクセルデータをそのまま`&[Pixel]`として参照できるかどうか
+    zero_copy: bool,
+
+    /// デコードした行
+    /// 
+    /// `zero_copy`の場合は空
+    rows: Box<[OnceLock<Box<[Pixel]>>]>,
+}
+
+impl MappedImage {
+    /// マップしたファイルのヘッダーを検証し、`MappedImage`を作成します。
+    fn new(map: Mmap) -> Result<MappedImage> {
+        if compress::is_compressed(&map) {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        let spec = header::check_header(&map)?;
+        let end = HEADER_SIZE + spec.num_pixels() * PIXEL_BYTES;
+        if map.len() < end {
+            return Err(Error::Truncated { expected: end, actual: map.len() });
+        }
+
+        // マップの先頭はページ境界に揃っているが、ヘッダーの後の位置も`Pixel`に揃っているか確認する
+        let data = &map[HEADER_SIZE..end];
+        let aligned = data.as_ptr().align_offset(align_of::<Pixel>()) == 0;
+        let zero_copy = aligned && is_native_endian(spec.pixel_endian);
+        let rows = if zero_copy {
+            Box::default()
+        } else {
+            (0..spec.height).map(|_| OnceLock::new()).collect()
+        };
+
+        Ok(MappedImage { map, spec, zero_copy, rows })
+    }
+
+    /// 画像の仕様を返します。
+    #[inline(always)]
+    pub fn spec(&self) -> &ImageSpec {
+        &self.spec
+    }
+
+    /// 画像の幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.spec.width
+    }
+
+    /// 画像の高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.spec.height
+    }
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    #[inline(always)]
+    pub fn transparent_color(&self) -> Option<Pixel> {
+        self.spec.transparent_color.map(Pixel)
+    }
+
+    /// ファイルのピクセルのエンディアンを返します。
+    #[inline(always)]
+    pub fn pixel_endian(&self) -> PixelEndian {
+        self.spec.pixel_endian
+    }
+
+    /// ファイルのピクセルデータをコピーせずに参照できる場合`true`を返します。
+    #[inline(always)]
+    pub fn is_zero_copy(&self) -> bool {
+        self.zero_copy
+    }
+
+    /// ヘッダーを除いたピクセルデータのバイト列を返します。
+    #[inline(always)]
+    fn data(&self) -> &[u8] {
+        &self.map[HEADER_SIZE..HEADER_SIZE + self.spec.num_pixels() * PIXEL_BYTES]
+    }
+
+    /// ファイルのピクセルデータをコピーせずに返します。
+    /// 
+    /// ピクセルのエンディアンが実行環境と異なる場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,no_run
+    /// # use limg::{Image, Result};
+    /// # fn main() -> Result<()> {
+    /// let atlas = unsafe { Image::open_mmap("atlas.limg")? };
+    /// 
+    /// if let Some(pixels) = atlas.pixels() {
+    ///     println!("{} pixels", pixels.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn pixels(&self) -> Option<&[Pixel]> {
+        if !self.zero_copy {
+            return None;
+        }
+
+        let data = self.data();
+        // `Pixel`は`u16`と同じ表現で、作成時に位置が揃っていることを確認している
+        Some(unsafe { core::slice::from_raw_parts(data.as_ptr().cast::<Pixel>(), data.len() / PIXEL_BYTES) })
+    }
+
+    /// `(x, y)`の位置のピクセルを返します。
+    /// 
+    /// 行をデコードせずにファイルから直接読み取ります。
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    #[inline]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<Pixel> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+
+        let offset = (y as usize * self.width() as usize + x as usize) * PIXEL_BYTES;
+        let bytes = &self.data()[offset..offset + PIXEL_BYTES];
+        Some(Pixel::from_bytes([bytes[0], bytes[1]], self.spec.pixel_endian))
+    }
+}
+
+impl GenericImage for MappedImage {
+    #[inline(always)]
+    fn width(&self) -> u16 {
+        MappedImage::width(self)
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u16 {
+        MappedImage::height(self)
+    }
+
+    #[inline(always)]
+    fn transparent_color(&self) -> Option<Pixel> {
+        MappedImage::transparent_color(self)
+    }
+
+    fn row(&self, y: u16) -> &[Pixel] {
+        assert!(y < self.height());
+        let width = self.width() as usize;
+        if let Some(pixels) = self.pixels() {
+            return &pixels[y as usize * width..(y as usize + 1) * width];
+        }
+
+        self.rows[y as usize].get_or_init(|| {
+            let start = y as usize * width * PIXEL_BYTES;
+            self.data()[start..start + width * PIXEL_BYTES]
+                .chunks_exact(PIXEL_BYTES)
+                .map(|bytes| Pixel::from_bytes([bytes[0], bytes[1]], self.spec.pixel_endian))
+                .collect()
+        })
+    }
+}
+
+impl Image {
+    /// `path`のLimg画像をメモリマップして開きます。
+    /// 
+    /// ファイル全体を読み込まないため、大きな画像の一部だけを使う場合に向いています。
+    /// ピクセルのエンディアンが実行環境と同じ場合はピクセルデータをコピーせずに参照し、
+    /// 異なる場合は行ごとにデコードします。画像データの後の余分なデータは無視されます。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正かIO操作に失敗した場合、`Error`を返します。
+    /// 圧縮された画像はマップできないため`Error::UnsupportedFormat`を返します。[`Image::open`]を使用してください。
+    /// 
+    /// # Safety
+    /// 
+    /// `MappedImage`が存在する間にファイルが変更されたり切り詰められたりしてはいけません。
+    /// 変更された場合の動作は未定義です。
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,no_run
+    /// # use limg::{GenericImageMut, Image, Rect, Result};
+    /// # fn main() -> Result<()> {
+    /// let atlas = unsafe { Image::open_mmap("atlas.limg")? };
+    /// 
+    /// let mut sprite = Image::new(32, 32);
+    /// sprite.blit_region(&atlas, Rect::new(64, 0, 32, 32), 0, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub unsafe fn open_mmap(path: impl AsRef<std::path::Path>) -> Result<MappedImage> {
+        let file = std::fs::File::open(path)?;
+        let map = unsafe { Mmap::map(&file)? };
+        MappedImage::new(map)
+    }
+}
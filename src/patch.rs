@@ -0,0 +1,458 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use limg_core::{PixelEndian, PIXEL_BYTES};
+
+use crate::image::Image;
+use crate::pixel::Pixel;
+use crate::rect::Rect;
+use crate::generic::{GenericImage, GenericImageMut};
+use crate::error::{Error, Result};
+
+/// パッチのシグネチャ
+const SIGNATURE: [u8; 4] = *b"LIMP";
+
+/// 対応しているパッチのバージョン
+const VERSION: u8 = 1;
+
+/// ピクセルデータがリトルエンディアンであることを示すフラグ
+const FLAG_LITTLE_ENDIAN: u8 = 0b01;
+
+/// パッチのヘッダーのサイズ
+/// 
+/// シグネチャ(4)、バージョン(1)、フラグ(1)、幅(2)、高さ(2)、領域数(2)の順に並びます。
+const PATCH_HEADER_SIZE: usize = 12;
+
+/// 領域のヘッダーのサイズ
+/// 
+/// x(2)、y(2)、幅(2)、高さ(2)の順に並び、その後に領域のピクセルが続きます。
+const REGION_HEADER_SIZE: usize = 8;
+
+/// [`GenericImage::diff`]で結合する領域の間隔
+pub(crate) const DEFAULT_THRESHOLD: u16 = 8;
+
+/// 結合処理用の`[x0, y0, x1, y1)`の範囲
+#[derive(Clone, Copy)]
+struct Bounds {
+    x0: u32,
+    y0: u32,
+    x1: u32,
+    y1: u32,
+}
+
+impl Bounds {
+    /// 縦横どちらの間隔も`gap`以下の場合`true`を返します。
+    #[inline(always)]
+    fn is_near(&self, other: &Bounds, gap: u32) -> bool {
+        self.x0 <= other.x1 + gap && other.x0 <= self.x1 + gap
+            && self.y0 <= other.y1 + gap && other.y0 <= self.y1 + gap
+    }
+
+    #[inline(always)]
+    fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    #[inline(always)]
+    fn to_rect(self) -> Rect {
+        Rect::new(self.x0 as u16, self.y0 as u16, (self.x1 - self.x0) as u16, (self.y1 - self.y0) as u16)
+    }
+}
+
+/// `a`と`b`で異なるピクセルを囲む矩形を返します。
+/// 
+/// 間隔が`threshold`ピクセル以下の矩形は結合されます。[`GenericImage::diff`]の実装です。
+pub(crate) fn diff<A, B>(a: &A, b: &B, threshold: u16) -> Vec<Rect>
+where
+    A: GenericImage + ?Sized,
+    B: GenericImage + ?Sized,
+{
+    assert!(a.width() == b.width() && a.height() == b.height(), "画像の大きさが一致しません");
+
+    let gap = threshold as u32;
+    let mut open: Vec<Bounds> = Vec::new();
+    let mut closed: Vec<Bounds> = Vec::new();
+    let mut spans: Vec<(u32, u32)> = Vec::new();
+
+    for y in 0..a.height() {
+        let y = y as u32;
+
+        // 現在の行から離れた矩形はこれ以上大きくならない
+        let mut i = 0;
+        while i < open.len() {
+            if open[i].y1 + gap < y {
+                closed.push(open.swap_remove(i));
+            } else {
+                i += 1;
+            }
+        }
+
+        // 行内で変化した区間を間隔を詰めながら集める
+        spans.clear();
+        for (x, (pa, pb)) in a.row(y as u16).iter().zip(b.row(y as u16)).enumerate() {
+            if pa == pb {
+                continue;
+            }
+            let x = x as u32;
+            match spans.last_mut() {
+                Some(last) if x <= last.1 + gap => last.1 = x + 1,
+                _ => spans.push((x, x + 1)),
+            }
+        }
+
+        for &(x0, x1) in &spans {
+            let mut merged = Bounds { x0, y0: y, x1, y1: y + 1 };
+            let mut i = 0;
+            while i < open.len() {
+                if open[i].is_near(&merged, gap) {
+                    merged = merged.union(&open.swap_remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+            open.push(merged);
+        }
+    }
+    closed.append(&mut open);
+
+    // 大きくなった矩形同士が近づいた場合は結合する
+    // 結合で大きくなった矩形は前にある矩形とも近づくため、結合がなくなるまで繰り返す
+    let mut merged = true;
+    while merged {
+        merged = false;
+        let mut i = 0;
+        while i < closed.len() {
+            let mut j = i + 1;
+            while j < closed.len() {
+                if closed[i].is_near(&closed[j], gap) {
+                    let other = closed.swap_remove(j);
+                    closed[i] = closed[i].union(&other);
+                    merged = true;
+                } else {
+                    j += 1;
+                }
+            }
+            i += 1;
+        }
+    }
+
+    closed.sort_unstable_by_key(|r| (r.y0, r.x0));
+    closed.into_iter().map(Bounds::to_rect).collect()
+}
+
+/// [`ImagePatch`]の1つの領域です。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatchRegion {
+    /// 画像内の範囲
+    rect: Rect,
+
+    /// 範囲内のピクセル
+    pixels: Box<[Pixel]>,
+}
+
+impl PatchRegion {
+    /// 画像内の範囲を返します。
+    #[inline(always)]
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// 範囲内のピクセルを行優先で返します。
+    #[inline(always)]
+    pub fn pixels(&self) -> &[Pixel] {
+        &self.pixels
+    }
+}
+
+/// 画像の変化した領域のピクセルだけを保持する差分です。
+/// 
+/// [`GenericImageMut::apply_patch`]で適用できます。
+/// エンコードしたパッチは12バイトのヘッダーの後に、各領域の範囲とピクセルが続きます。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{GenericImageMut, Image, ImagePatch, Pixel, Result};
+/// # fn main() -> Result<()> {
+/// let old = Image::new(320, 240);
+/// let mut new = old.clone();
+/// new[(10, 10)] = Pixel::RED;
+/// new[(300, 200)] = Pixel::BLUE;
+/// 
+/// let patch = ImagePatch::new(&old, &new);
+/// assert_eq!(patch.regions().len(), 2);
+/// 
+/// let mut buf = vec![0u8; patch.encoded_size()];
+/// patch.to_buffer(&mut buf)?;
+/// 
+/// let mut display = old.clone();
+/// display.apply_patch(&ImagePatch::from_buffer(&buf)?)?;
+/// assert_eq!(display, new);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImagePatch {
+    /// 適用先の画像の幅
+    width: u16,
+
+    /// 適用先の画像の高さ
+    height: u16,
+
+    /// 変化した領域
+    regions: Vec<PatchRegion>,
+}
+
+impl ImagePatch {
+    /// `old`を`new`に更新するパッチを作成します。
+    /// 
+    /// 領域は[`GenericImage::diff`]で求めます。
+    /// 
+    /// # Panics
+    /// 
+    /// `old`と`new`の大きさが異なる場合パニックします。
+    #[inline]
+    pub fn new(old: &(impl GenericImage + ?Sized), new: &(impl GenericImage + ?Sized)) -> ImagePatch {
+        ImagePatch::from_rects(new, &diff(old, new, DEFAULT_THRESHOLD))
+    }
+
+    /// `image`の`rects`の範囲のピクセルからパッチを作成します。
+    /// 
+    /// `rects`は画像の範囲に切り取られ、空になった範囲は無視されます。
+    pub fn from_rects(image: &(impl GenericImage + ?Sized), rects: &[Rect]) -> ImagePatch {
+        let bounds = Rect::new(0, 0, image.width(), image.height());
+        let regions = rects.iter().filter_map(|rect| rect.intersection(&bounds)).map(|rect| {
+            let mut pixels = Vec::with_capacity(rect.width as usize * rect.height as usize);
+            for y in rect.y..rect.y + rect.height {
+                pixels.extend_from_slice(&image.row(y)[rect.x as usize..(rect.x + rect.width) as usize]);
+            }
+            PatchRegion { rect, pixels: pixels.into_boxed_slice() }
+        }).collect();
+
+        ImagePatch { width: image.width(), height: image.height(), regions }
+    }
+
+    /// 適用先の画像の幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// 適用先の画像の高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// 変化した領域がない場合`true`を返します。
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// 領域を返す`Iterator`を返します。
+    #[inline(always)]
+    pub fn regions(&self) -> core::slice::Iter<'_, PatchRegion> {
+        self.regions.iter()
+    }
+
+    /// エンコードした場合のバイト数を返します。
+    pub fn encoded_size(&self) -> usize {
+        PATCH_HEADER_SIZE + self.regions.iter().map(|region| REGION_HEADER_SIZE + region.pixels.len() * PIXEL_BYTES).sum::<usize>()
+    }
+
+    /// パッチをエンコードし`buf`に書き込みます。
+    /// 
+    /// ピクセルはリトルエンディアンで書き込まれます。
+    /// 
+    /// # Errors
+    /// 
+    /// `buf`の長さが[`encoded_size`]より短い場合、`Error`を返します。
+    /// 
+    /// [`encoded_size`]: ImagePatch::encoded_size
+    #[inline(always)]
+    pub fn to_buffer(&self, buf: &mut impl AsMut<[u8]>) -> Result<()> {
+        self.to_buffer_with_endian(buf, PixelEndian::Little)
+    }
+
+    /// パッチを指定された`endian`でピクセルエンコードし`buf`に書き込みます。
+    /// 
+    /// # Errors
+    /// 
+    /// `buf`の長さが[`encoded_size`]より短いか、領域が65535個を超える場合、`Error`を返します。
+    /// 
+    /// [`encoded_size`]: ImagePatch::encoded_size
+    pub fn to_buffer_with_endian(&self, buf: &mut impl AsMut<[u8]>, endian: PixelEndian) -> Result<()> {
+        let buf = buf.as_mut();
+        if buf.len() < self.encoded_size() {
+            return Err(Error::OutputBufferTooSmall);
+        }
+        let region_count = u16::try_from(self.regions.len()).map_err(|_| Error::SizeMismatch)?;
+
+        buf[0..4].copy_from_slice(&SIGNATURE);
+        buf[4] = VERSION;
+        buf[5] = if matches!(endian, PixelEndian::Little) { FLAG_LITTLE_ENDIAN } else { 0 };
+        buf[6..8].copy_from_slice(&self.width.to_le_bytes());
+        buf[8..10].copy_from_slice(&self.height.to_le_bytes());
+        buf[10..12].copy_from_slice(&region_count.to_le_bytes());
+
+        let mut offset = PATCH_HEADER_SIZE;
+        for region in &self.regions {
+            let Rect { x, y, width, height } = region.rect;
+            for (i, value) in [x, y, width, height].into_iter().enumerate() {
+                buf[offset + i * 2..offset + i * 2 + 2].copy_from_slice(&value.to_le_bytes());
+            }
+            offset += REGION_HEADER_SIZE;
+
+            for pixel in region.pixels.iter() {
+                buf[offset..offset + PIXEL_BYTES].copy_from_slice(&pixel.to_bytes(endian));
+                offset += PIXEL_BYTES;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `buf`からパッチを読み取ります。
+    /// 
+    /// # Errors
+    /// 
+    /// データが不正か、領域が画像の範囲外にある場合、`Error`を返します。
+    pub fn from_buffer(buf: impl AsRef<[u8]>) -> Result<ImagePatch> {
+        let buf = buf.as_ref();
+        if buf.len() < PATCH_HEADER_SIZE {
+            return Err(Error::InputBufferTooSmall);
+        }
+        if buf[0..4] != SIGNATURE || buf[4] != VERSION || buf[5] & !FLAG_LITTLE_ENDIAN != 0 {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        let read_u16 = |i: usize| u16::from_le_bytes([buf[i], buf[i + 1]]);
+        let endian = if buf[5] & FLAG_LITTLE_ENDIAN != 0 { PixelEndian::Little } else { PixelEndian::Big };
+        let width = read_u16(6);
+        let height = read_u16(8);
+        let region_count = read_u16(10) as usize;
+        let bounds = Rect::new(0, 0, width, height);
+
+        let mut regions = Vec::with_capacity(region_count);
+        let mut offset = PATCH_HEADER_SIZE;
+        for _ in 0..region_count {
+            if buf.len() < offset + REGION_HEADER_SIZE {
+                return Err(Error::InputBufferTooSmall);
+            }
+            let rect = Rect::new(read_u16(offset), read_u16(offset + 2), read_u16(offset + 4), read_u16(offset + 6));
+            if rect.is_empty() || rect.intersection(&bounds) != Some(rect) {
+                return Err(Error::UnsupportedFormat);
+            }
+            offset += REGION_HEADER_SIZE;
+
+            let len = rect.width as usize * rect.height as usize * PIXEL_BYTES;
+            let data = buf.get(offset..offset + len).ok_or(Error::InputBufferTooSmall)?;
+            let pixels = data.chunks_exact(PIXEL_BYTES).map(|b| Pixel::from_bytes([b[0], b[1]], endian)).collect();
+            regions.push(PatchRegion { rect, pixels });
+            offset += len;
+        }
+
+        Ok(ImagePatch { width, height, regions })
+    }
+}
+
+impl Image {
+    /// `other`と異なるピクセルを囲む矩形を返します。
+    /// 
+    /// [`GenericImage::diff`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Panics
+    /// 
+    /// `other`と大きさが異なる場合パニックします。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Rect};
+    /// let old = Image::new(64, 64);
+    /// let mut new = old.clone();
+    /// new[(2, 2)] = Pixel::RED;
+    /// 
+    /// assert_eq!(old.diff(&new), vec![Rect::new(2, 2, 1, 1)]);
+    /// ```
+    #[inline(always)]
+    pub fn diff(&self, other: &impl GenericImage) -> Vec<Rect> {
+        GenericImage::diff(self, other)
+    }
+
+    /// `other`と異なるピクセルを囲む矩形を、間隔が`threshold`ピクセル以下のものを結合して返します。
+    /// 
+    /// [`GenericImage::diff_with_threshold`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Panics
+    /// 
+    /// `other`と大きさが異なる場合パニックします。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Rect};
+    /// let old = Image::new(64, 64);
+    /// let mut new = old.clone();
+    /// new[(2, 2)] = Pixel::RED;
+    /// new[(6, 3)] = Pixel::RED;
+    /// 
+    /// assert_eq!(old.diff_with_threshold(&new, 3), vec![Rect::new(2, 2, 5, 2)]);
+    /// ```
+    #[inline(always)]
+    pub fn diff_with_threshold(&self, other: &impl GenericImage, threshold: u16) -> Vec<Rect> {
+        GenericImage::diff_with_threshold(self, other, threshold)
+    }
+
+    /// `patch`の各領域のピクセルを画像に書き込みます。
+    /// 
+    /// [`GenericImageMut::apply_patch`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Errors
+    /// 
+    /// `patch`の適用先と画像の大きさが異なる場合、`Error::SizeMismatch`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, ImagePatch, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut display = Image::new(16, 16);
+    /// let mut frame = display.clone();
+    /// frame[(4, 4)] = Pixel::GREEN;
+    /// 
+    /// display.apply_patch(&ImagePatch::new(&display, &frame))?;
+    /// 
+    /// assert_eq!(display, frame);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn apply_patch(&mut self, patch: &ImagePatch) -> Result<()> {
+        GenericImageMut::apply_patch(self, patch)
+    }
+}
+
+/// `patch`を`image`に適用します。
+/// 
+/// [`GenericImageMut::apply_patch`]の実装です。
+pub(crate) fn apply_patch<I: GenericImageMut + ?Sized>(image: &mut I, patch: &ImagePatch) -> Result<()> {
+    if image.width() != patch.width || image.height() != patch.height {
+        return Err(Error::SizeMismatch);
+    }
+
+    for region in &patch.regions {
+        let Rect { x, y, width, .. } = region.rect;
+        for (row, src) in (y..).zip(region.pixels.chunks_exact(width as usize)) {
+            image.row_mut(row)[x as usize..x as usize + width as usize].copy_from_slice(src);
+        }
+    }
+
+    Ok(())
+}
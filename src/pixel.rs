@@ -1,5 +1,8 @@
+use alloc::vec;
+
 use ::core::fmt::*;
-use limg_core::{pixel_to_rgb, rgb_to_pixel};
+use crate::error::Error;
+use limg_core::{pixel_to_rgb, rgb_to_pixel, PixelEndian};
 
 /// ピクセル生成マクロです。
 /// 
@@ -22,14 +25,15 @@ macro_rules! px {
 
 /// Limg画像で使用するピクセルです。
 /// 
-/// RGB565ピクセルと同等です。
+/// RGB565ピクセルと同等で、`u16`と同じメモリ表現を持ちます。
 #[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
 pub struct Pixel(pub u16);
 
 impl Pixel {
-    const R_MASK: u16 = 0xF800;
-    const G_MASK: u16 = 0x07E0;
-    const B_MASK: u16 = 0x001F;
+    pub(crate) const R_MASK: u16 = 0xF800;
+    pub(crate) const G_MASK: u16 = 0x07E0;
+    pub(crate) const B_MASK: u16 = 0x001F;
 
     /// 黒のピクセル
     pub const BLACK:   Pixel = px!(0x0000);
@@ -194,6 +198,38 @@ impl Pixel {
     pub const fn into_rgb(self) -> [u8; 3] {
         pixel_to_rgb(self.0)
     }
+
+    /// 指定された`endian`のバイト列からピクセルを生成します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Pixel, PixelEndian};
+    /// assert_eq!(Pixel::from_bytes([0x1F, 0x00], PixelEndian::Little), Pixel::BLUE);
+    /// assert_eq!(Pixel::from_bytes([0x00, 0x1F], PixelEndian::Big), Pixel::BLUE);
+    /// ```
+    pub const fn from_bytes(bytes: [u8; 2], endian: PixelEndian) -> Pixel {
+        match endian {
+            PixelEndian::Little => Pixel(u16::from_le_bytes(bytes)),
+            PixelEndian::Big => Pixel(u16::from_be_bytes(bytes)),
+        }
+    }
+
+    /// ピクセルを指定された`endian`のバイト列に変換します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Pixel, PixelEndian};
+    /// assert_eq!(Pixel::BLUE.to_bytes(PixelEndian::Little), [0x1F, 0x00]);
+    /// assert_eq!(Pixel::BLUE.to_bytes(PixelEndian::Big), [0x00, 0x1F]);
+    /// ```
+    pub const fn to_bytes(self, endian: PixelEndian) -> [u8; 2] {
+        match endian {
+            PixelEndian::Little => self.0.to_le_bytes(),
+            PixelEndian::Big => self.0.to_be_bytes(),
+        }
+    }
 }
 
 impl From<u16> for Pixel {
@@ -231,3 +267,55 @@ impl UpperHex for Pixel {
         UpperHex::fmt(&self.0, f)
     }
 }
+
+/// `pixels`に含まれない色を返します。
+/// 
+/// マゼンタが使われていなければマゼンタを優先します。すべての色が使われている場合は`None`を返します。
+pub(crate) fn unused_color(pixels: impl Iterator<Item = Pixel>) -> Option<Pixel> {
+    let mut used = vec![0u64; 0x10000 / 64];
+    for pixel in pixels {
+        used[pixel.0 as usize / 64] |= 1 << (pixel.0 % 64);
+    }
+
+    let is_unused = |color: u16| used[color as usize / 64] & (1 << (color % 64)) == 0;
+
+    if is_unused(Pixel::MAGENTA.0) {
+        return Some(Pixel::MAGENTA);
+    }
+    (0..=u16::MAX).find(|&color| is_unused(color)).map(Pixel)
+}
+
+/// `transparent`が`true`を返す位置のピクセルを透明色に置き換え、透明色を返します。
+/// 
+/// 透明なピクセルがすべて同じ色で、その色が不透明なピクセルに使われていなければその色を透明色にします。
+/// それ以外の場合は使われていない色を選びます。透明なピクセルがない場合は`None`を返します。
+pub(crate) fn key_transparent_pixels(pixels: &mut [Pixel], transparent: impl Fn(usize) -> bool) -> ::core::result::Result<Option<Pixel>, Error> {
+    let mut candidate = None;
+    let mut uniform = true;
+    for (_, &pixel) in pixels.iter().enumerate().filter(|&(i, _)| transparent(i)) {
+        match candidate {
+            None => candidate = Some(pixel),
+            Some(color) if color != pixel => uniform = false,
+            Some(_) => {},
+        }
+    }
+
+    let Some(candidate) = candidate else {
+        return Ok(None);
+    };
+
+    let opaque = || pixels.iter().enumerate().filter(|&(i, _)| !transparent(i)).map(|(_, &p)| p);
+    let key = if uniform && !opaque().any(|p| p == candidate) {
+        candidate
+    } else {
+        unused_color(opaque()).ok_or(Error::UnsupportedFormat)?
+    };
+
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        if transparent(i) {
+            *pixel = key;
+        }
+    }
+
+    Ok(Some(key))
+}
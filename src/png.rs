@@ -0,0 +1,107 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::image::Image;
+use crate::error::{Error, Result};
+
+impl Image {
+    /// `reader`からPNG画像を読み取り、`Image`を作成します。
+    /// 
+    /// 色情報はRGB565に減色されます。
+    /// 完全に透明なピクセルは透明色になります。透明なピクセルの色が1色で他に使われていなければその色が、
+    /// それ以外の場合は画像内で使われていない色が透明色に選ばれます。
+    /// 完全に透明なピクセルがない場合、透明色は`None`になります。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正な場合は`Error::PngError`、IO操作に失敗した場合は`Error::IoError`を返します。
+    /// 幅か高さが`u16::MAX`を超える場合は、ピクセルデータを読み込む前に`Error::UnsupportedFormat`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut image = Image::with_transparent_color(2, 1, Pixel::WHITE);
+    /// image[(0, 0)] = Pixel::RED;
+    /// image[(1, 0)] = Pixel::WHITE;
+    /// 
+    /// let mut buf = Vec::new();
+    /// image.to_png(&mut buf)?;
+    /// let image = Image::from_png(buf.as_slice())?;
+    /// 
+    /// assert_eq!(image[(0, 0)], Pixel::RED);
+    /// assert_eq!(Some(image[(1, 0)]), image.transparent_color());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_png(reader: impl std::io::Read) -> Result<Image> {
+        let mut decoder = ::png::Decoder::new(reader);
+        decoder.set_transformations(::png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+
+        // 大きすぎる画像のバッファを確保しないよう、先に大きさを検証する
+        let width = u16::try_from(reader.info().width).map_err(|_| Error::UnsupportedFormat)?;
+        let height = u16::try_from(reader.info().height).map_err(|_| Error::UnsupportedFormat)?;
+
+        let mut buf = vec![0u8; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        let data = &buf[..info.buffer_size()];
+
+        // RGBA8888に正規化
+        let rgba: Vec<u8> = match info.color_type {
+            ::png::ColorType::Grayscale => data.iter().flat_map(|&l| [l, l, l, 0xFF]).collect(),
+            ::png::ColorType::GrayscaleAlpha => data.chunks_exact(2).flat_map(|c| [c[0], c[0], c[0], c[1]]).collect(),
+            ::png::ColorType::Rgb => data.chunks_exact(3).flat_map(|c| [c[0], c[1], c[2], 0xFF]).collect(),
+            ::png::ColorType::Rgba => data.to_vec(),
+            ::png::ColorType::Indexed => return Err(Error::UnsupportedFormat),
+        };
+
+        Image::from_rgba8888(width, height, &rgba)
+    }
+
+    /// 画像をPNG形式でエンコードし`writer`に書き込みます。
+    /// 
+    /// 透明色がある場合はRGBA、ない場合はRGBで書き込まれます。透明色のピクセルはアルファ値`0`になります。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正かIO操作に失敗した場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,no_run
+    /// # use limg::{Image, Result};
+    /// # fn main() -> Result<()> {
+    /// # let image = Image::new(10, 10);
+    /// let mut writer = std::fs::File::create("image.png")?;
+    /// image.to_png(&mut writer)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_png(&self, writer: &mut impl std::io::Write) -> Result<()> {
+        if self.width() == 0 || self.height() == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+
+        let mut encoder = ::png::Encoder::new(writer, self.width() as u32, self.height() as u32);
+        encoder.set_depth(::png::BitDepth::Eight);
+
+        let data = match self.transparent_color() {
+            Some(_) => {
+                encoder.set_color(::png::ColorType::Rgba);
+                self.to_rgba8888_vec()
+            },
+            None => {
+                encoder.set_color(::png::ColorType::Rgb);
+                self.to_rgb888_vec()
+            },
+        };
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::image::Image;
+use crate::pixel::{Pixel, key_transparent_pixels};
+use crate::error::{Error, Result};
+
+/// Netpbm形式の種類です。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PnmFormat {
+    /// バイナリのPPM(P6)
+    Ppm,
+
+    /// テキストのPPM(P3)
+    PpmAscii,
+
+    /// PAM(P7)
+    /// 
+    /// 透明色がある場合は`RGB_ALPHA`、ない場合は`RGB`で書き込まれます。
+    Pam,
+}
+
+/// バッファへの書き込み位置を管理します。
+/// 
+/// `buf`が`None`の場合は書き込まずにバイト数だけを数えます。
+struct Cursor<'a> {
+    buf: Option<&'a mut [u8]>,
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Result<()> {
+        if let Some(buf) = self.buf.as_deref_mut() {
+            let dst = buf.get_mut(self.pos..self.pos + bytes.len()).ok_or(Error::OutputBufferTooSmall)?;
+            dst.copy_from_slice(bytes);
+        }
+        self.pos += bytes.len();
+        Ok(())
+    }
+}
+
+impl Write for Cursor<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.put(s.as_bytes()).map_err(|_| core::fmt::Error)
+    }
+}
+
+/// Netpbmヘッダーのトークンを読み取ります。
+struct Parser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// 空白とコメントを読み飛ばします。
+    fn skip_space(&mut self) {
+        while let Some(&c) = self.buf.get(self.pos) {
+            if c == b'#' {
+                while self.buf.get(self.pos).is_some_and(|&c| c != b'\n') {
+                    self.pos += 1;
+                }
+            } else if c.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<&'a [u8]> {
+        self.skip_space();
+        let start = self.pos;
+        while self.buf.get(self.pos).is_some_and(|c| !c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            return Err(Error::InputBufferTooSmall);
+        }
+        Ok(&self.buf[start..self.pos])
+    }
+
+    fn number(&mut self) -> Result<u32> {
+        let token = self.token()?;
+        token.iter().try_fold(0u32, |n, &c| {
+            if !c.is_ascii_digit() {
+                return None;
+            }
+            n.checked_mul(10)?.checked_add((c - b'0') as u32)
+        }).ok_or(Error::UnsupportedFormat)
+    }
+
+    /// ヘッダー終端の空白1バイトを読み飛ばします。
+    fn end_header(&mut self) -> Result<()> {
+        match self.buf.get(self.pos) {
+            Some(c) if c.is_ascii_whitespace() => {
+                self.pos += 1;
+                Ok(())
+            },
+            Some(_) => Err(Error::UnsupportedFormat),
+            None => Err(Error::InputBufferTooSmall),
+        }
+    }
+
+    /// サンプル値を1つ読み取ります。
+    fn sample(&mut self, ascii: bool, maxval: u32) -> Result<u32> {
+        let value = if ascii {
+            self.number()?
+        } else if maxval > 0xFF {
+            let bytes = self.buf.get(self.pos..self.pos + 2).ok_or(Error::InputBufferTooSmall)?;
+            self.pos += 2;
+            u16::from_be_bytes([bytes[0], bytes[1]]) as u32
+        } else {
+            let byte = *self.buf.get(self.pos).ok_or(Error::InputBufferTooSmall)?;
+            self.pos += 1;
+            byte as u32
+        };
+
+        if value > maxval {
+            return Err(Error::UnsupportedFormat);
+        }
+        Ok(value)
+    }
+}
+
+impl Image {
+    /// `format`で書き込まれるNetpbm画像のバイト数を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, PnmFormat};
+    /// let image = Image::new(2, 2);
+    /// 
+    /// assert_eq!(image.pnm_size(PnmFormat::Ppm), b"P6\n2 2\n255\n".len() + 2 * 2 * 3);
+    /// ```
+    pub fn pnm_size(&self, format: PnmFormat) -> usize {
+        let mut cursor = Cursor { buf: None, pos: 0 };
+        // 数えるだけなので失敗しない
+        let _ = self.write_pnm(&mut cursor, format);
+        cursor.pos
+    }
+
+    /// 画像を`format`のNetpbm形式でエンコードし`buf`に書き込みます。
+    /// 
+    /// 色情報は8ビットに拡張されます。書き込んだバイト数を返します。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像サイズが0か`buf`の長さが[`pnm_size`]より短い場合、`Error`を返します。
+    /// 
+    /// [`pnm_size`]: Image::pnm_size
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, PnmFormat, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut image = Image::with_transparent_color(2, 1, Pixel::BLACK);
+    /// image[(1, 0)] = Pixel::YELLOW;
+    /// 
+    /// let mut buf = [0u8; 128];
+    /// let size = image.to_pnm(&mut buf, PnmFormat::Pam)?;
+    /// 
+    /// assert_eq!(Image::from_pnm(&buf[..size])?, image);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_pnm(&self, buf: &mut impl AsMut<[u8]>, format: PnmFormat) -> Result<usize> {
+        if self.width() == 0 || self.height() == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+
+        let mut cursor = Cursor { buf: Some(buf.as_mut()), pos: 0 };
+        self.write_pnm(&mut cursor, format)?;
+        Ok(cursor.pos)
+    }
+
+    fn write_pnm(&self, cursor: &mut Cursor, format: PnmFormat) -> Result<()> {
+        let (width, height) = (self.width(), self.height());
+
+        match format {
+            PnmFormat::Ppm => {
+                write!(cursor, "P6\n{} {}\n255\n", width, height).map_err(|_| Error::OutputBufferTooSmall)?;
+                for pixel in self.pixels() {
+                    cursor.put(&pixel.into_rgb())?;
+                }
+            },
+            PnmFormat::PpmAscii => {
+                write!(cursor, "P3\n{} {}\n255\n", width, height).map_err(|_| Error::OutputBufferTooSmall)?;
+                for pixel in self.pixels() {
+                    let [r, g, b] = pixel.into_rgb();
+                    writeln!(cursor, "{} {} {}", r, g, b).map_err(|_| Error::OutputBufferTooSmall)?;
+                }
+            },
+            PnmFormat::Pam => {
+                let (depth, tuple_type) = match self.transparent_color() {
+                    Some(_) => (4, "RGB_ALPHA"),
+                    None => (3, "RGB"),
+                };
+                write!(cursor, "P7\nWIDTH {}\nHEIGHT {}\nDEPTH {}\nMAXVAL 255\nTUPLTYPE {}\nENDHDR\n", width, height, depth, tuple_type)
+                    .map_err(|_| Error::OutputBufferTooSmall)?;
+                for &pixel in self.pixels() {
+                    cursor.put(&pixel.into_rgb())?;
+                    if let Some(key) = self.transparent_color() {
+                        cursor.put(&[if pixel == key { 0x00 } else { 0xFF }])?;
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// `buf`からNetpbm画像(PGM、PPM、PAM)を読み取り、`Image`を作成します。
+    /// 
+    /// P2、P3、P5、P6、P7に対応しています。色情報はRGB565に減色されます。
+    /// PAMのアルファ値が`0`のピクセルは透明色になります。透明なピクセルの色が1色で他に使われていなければその色が、
+    /// それ以外の場合は画像内で使われていない色が透明色に選ばれます。
+    /// 
+    /// # Errors
+    /// 
+    /// 画像データが不正か対応していない形式の場合、`Error`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Result};
+    /// # fn main() -> Result<()> {
+    /// let image = Image::from_pnm(b"P3\n2 1\n# comment\n255\n255 0 0  0 0 255\n")?;
+    /// 
+    /// assert_eq!(image[(0, 0)], Pixel::RED);
+    /// assert_eq!(image[(1, 0)], Pixel::BLUE);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_pnm(buf: impl AsRef<[u8]>) -> Result<Image> {
+        let mut parser = Parser { buf: buf.as_ref(), pos: 0 };
+
+        let magic = parser.token()?;
+        let (width, height, depth, maxval, ascii) = match magic {
+            b"P2" | b"P3" | b"P5" | b"P6" => {
+                let width = parser.number()?;
+                let height = parser.number()?;
+                let maxval = parser.number()?;
+                parser.end_header()?;
+
+                let depth = if matches!(magic, b"P2" | b"P5") { 1 } else { 3 };
+                (width, height, depth, maxval, matches!(magic, b"P2" | b"P3"))
+            },
+            b"P7" => {
+                let (mut width, mut height, mut depth, mut maxval) = (0, 0, 0, 0);
+                loop {
+                    match parser.token()? {
+                        b"WIDTH" => width = parser.number()?,
+                        b"HEIGHT" => height = parser.number()?,
+                        b"DEPTH" => depth = parser.number()?,
+                        b"MAXVAL" => maxval = parser.number()?,
+                        b"TUPLTYPE" => { parser.token()?; },
+                        b"ENDHDR" => break,
+                        _ => return Err(Error::UnsupportedFormat),
+                    }
+                }
+                parser.end_header()?;
+
+                (width, height, depth, maxval, false)
+            },
+            _ => return Err(Error::UnsupportedFormat),
+        };
+
+        if width == 0 || height == 0 {
+            return Err(Error::ZeroImageDimensions);
+        }
+        if !(1..=4).contains(&depth) || !(1..=0xFFFF).contains(&maxval) {
+            return Err(Error::UnsupportedFormat);
+        }
+        let width = u16::try_from(width).map_err(|_| Error::UnsupportedFormat)?;
+        let height = u16::try_from(height).map_err(|_| Error::UnsupportedFormat)?;
+
+        let num_pixels = width as usize * height as usize;
+        let mut pixels = if ascii {
+            // テキスト形式はサンプルの長さが決まらないため、読み取りながら確保する
+            Vec::new()
+        } else {
+            let bytes_per_sample = if maxval > 0xFF { 2 } else { 1 };
+            if parser.buf.len() - parser.pos < num_pixels * depth as usize * bytes_per_sample {
+                return Err(Error::InputBufferTooSmall);
+            }
+            Vec::with_capacity(num_pixels)
+        };
+        let mut alpha = Vec::new();
+
+        let scale = |v: u32| ((v * 255 + maxval / 2) / maxval) as u8;
+
+        for _ in 0..num_pixels {
+            let rgb = if depth < 3 {
+                let l = scale(parser.sample(ascii, maxval)?);
+                [l, l, l]
+            } else {
+                let r = scale(parser.sample(ascii, maxval)?);
+                let g = scale(parser.sample(ascii, maxval)?);
+                let b = scale(parser.sample(ascii, maxval)?);
+                [r, g, b]
+            };
+            pixels.push(Pixel::from_rgb(rgb));
+
+            if depth == 2 || depth == 4 {
+                alpha.push(parser.sample(ascii, maxval)?);
+            }
+        }
+
+        let transparent_color = key_transparent_pixels(&mut pixels, |i| alpha.get(i) == Some(&0))?;
+
+        Ok(Image::from_parts(width, height, transparent_color, pixels.into_boxed_slice()))
+    }
+}
@@ -0,0 +1,213 @@
+use limg_core::{ImageSpec, PixelEndian, HEADER_SIZE, PIXEL_BYTES};
+
+use crate::pixel::Pixel;
+use crate::header;
+use crate::compress::{self, Compression, COMPRESSED_HEADER_SIZE};
+use crate::error::Result;
+
+/// ヘッダーから読み取った画像の情報です。
+/// 
+/// [`probe`]、[`probe_read`]、[`probe_buffer`]で取得できます。ピクセルはデコードされません。
+/// 
+/// [`probe`]: crate::probe
+/// [`probe_read`]: crate::probe_read
+/// [`probe_buffer`]: crate::probe_buffer
+#[derive(Clone, Copy, Debug)]
+pub struct ImageInfo {
+    /// 画像の仕様
+    spec: ImageSpec,
+
+    /// 圧縮方式
+    /// 
+    /// 圧縮されていない場合`None`
+    compression: Option<Compression>,
+
+    /// ヘッダーから求めたファイルのバイト数
+    encoded_size: usize,
+
+    /// 実際のファイルのバイト数
+    /// 
+    /// 分からない場合`None`
+    file_size: Option<u64>,
+}
+
+impl ImageInfo {
+    /// ヘッダーを読み取ります。
+    /// 
+    /// 圧縮された画像の場合、`header`は[`COMPRESSED_HEADER_SIZE`]バイト必要です。
+    fn from_header(header: &[u8]) -> Result<ImageInfo> {
+        if compress::is_compressed(header) {
+            let header = compress::decode_header(header)?;
+            return Ok(ImageInfo {
+                spec: header.spec,
+                compression: Some(header.compression),
+                encoded_size: COMPRESSED_HEADER_SIZE + header.payload_len,
+                file_size: None,
+            });
+        }
+
+        let spec = header::check_header(header)?;
+        let encoded_size = HEADER_SIZE + spec.num_pixels() * PIXEL_BYTES;
+        Ok(ImageInfo { spec, compression: None, encoded_size, file_size: None })
+    }
+
+    /// 画像の仕様を返します。
+    #[inline(always)]
+    pub fn spec(&self) -> &ImageSpec {
+        &self.spec
+    }
+
+    /// 画像の幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.spec.width
+    }
+
+    /// 画像の高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.spec.height
+    }
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    #[inline(always)]
+    pub fn transparent_color(&self) -> Option<Pixel> {
+        self.spec.transparent_color.map(Pixel)
+    }
+
+    /// ピクセルのエンディアンを返します。
+    #[inline(always)]
+    pub fn pixel_endian(&self) -> PixelEndian {
+        self.spec.pixel_endian
+    }
+
+    /// 圧縮方式を返します。
+    /// 
+    /// 圧縮されていない場合`None`になります。
+    #[inline(always)]
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    /// ヘッダーから求めたファイルのバイト数を返します。
+    #[inline(always)]
+    pub fn encoded_size(&self) -> usize {
+        self.encoded_size
+    }
+
+    /// 実際のファイルのバイト数を返します。
+    /// 
+    /// [`probe_read`]で読み取った場合など、分からない場合は`None`になります。
+    /// 
+    /// [`probe_read`]: crate::probe_read
+    #[inline(always)]
+    pub fn file_size(&self) -> Option<u64> {
+        self.file_size
+    }
+
+    /// ファイルが[`encoded_size`]より短い場合`true`を返します。
+    /// 
+    /// ファイルのバイト数が分からない場合は`false`を返します。
+    /// 
+    /// [`encoded_size`]: ImageInfo::encoded_size
+    #[inline(always)]
+    pub fn is_truncated(&self) -> bool {
+        self.file_size.is_some_and(|size| size < self.encoded_size as u64)
+    }
+
+    /// ファイルが[`encoded_size`]より長い場合`true`を返します。
+    /// 
+    /// ファイルのバイト数が分からない場合は`false`を返します。
+    /// 
+    /// [`encoded_size`]: ImageInfo::encoded_size
+    #[inline(always)]
+    pub fn has_trailing_bytes(&self) -> bool {
+        self.file_size.is_some_and(|size| size > self.encoded_size as u64)
+    }
+}
+
+/// `buf`のヘッダーだけを読み取り、画像の情報を返します。
+/// 
+/// ピクセルはデコードしません。`buf`の長さがファイルのバイト数として扱われます。
+/// 
+/// # Errors
+/// 
+/// ヘッダーが不正な場合、`Error`を返します。
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use limg::{Image, Result};
+/// # fn main() -> Result<()> {
+/// # let image = Image::new(16, 8);
+/// # let mut buf = vec![0u8; 12 + 16 * 8 * 2];
+/// # image.to_buffer(&mut buf)?;
+/// let info = limg::probe_buffer(&buf)?;
+/// 
+/// assert_eq!((info.width(), info.height()), (16, 8));
+/// assert_eq!(info.encoded_size(), buf.len());
+/// assert!(limg::probe_buffer(&buf[..100])?.is_truncated());
+/// # Ok(())
+/// # }
+/// ```
+pub fn probe_buffer(buf: impl AsRef<[u8]>) -> Result<ImageInfo> {
+    let buf = buf.as_ref();
+    let mut info = ImageInfo::from_header(buf)?;
+    info.file_size = Some(buf.len() as u64);
+    Ok(info)
+}
+
+/// `reader`からヘッダーだけを読み取り、画像の情報を返します。
+/// 
+/// 読み取るのは[`HEADER_SIZE`]バイト、圧縮された画像の場合は16バイトだけです。
+/// ファイルのバイト数は分からないため、[`ImageInfo::file_size`]は`None`になります。
+/// 
+/// # Errors
+/// 
+/// ヘッダーが不正かIO操作に失敗した場合、`Error`を返します。
+/// 
+/// [`HEADER_SIZE`]: limg_core::HEADER_SIZE
+#[cfg(feature = "std")]
+pub fn probe_read(mut reader: impl std::io::Read) -> Result<ImageInfo> {
+    let mut header = [0u8; COMPRESSED_HEADER_SIZE];
+    reader.read_exact(&mut header[..HEADER_SIZE])?;
+    if compress::is_compressed(&header) {
+        reader.read_exact(&mut header[HEADER_SIZE..])?;
+        return ImageInfo::from_header(&header);
+    }
+
+    ImageInfo::from_header(&header[..HEADER_SIZE])
+}
+
+/// `path`のファイルのヘッダーだけを読み取り、画像の情報を返します。
+/// 
+/// ピクセルは読み込みません。ファイルのバイト数はメタデータから取得します。
+/// 
+/// # Errors
+/// 
+/// ヘッダーが不正かIO操作に失敗した場合、`Error`を返します。
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// # use limg::Result;
+/// # fn main() -> Result<()> {
+/// let info = limg::probe("image.limg")?;
+/// 
+/// println!("{}x{}", info.width(), info.height());
+/// if info.is_truncated() {
+///     println!("truncated");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub fn probe(path: impl AsRef<std::path::Path>) -> Result<ImageInfo> {
+    let file = std::fs::File::open(path)?;
+    let file_size = file.metadata()?.len();
+    let mut info = probe_read(file)?;
+    info.file_size = Some(file_size);
+    Ok(info)
+}
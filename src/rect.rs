@@ -0,0 +1,93 @@
+/// 画像上の矩形領域です。
+/// 
+/// `(x, y)`は矩形の左上隅を表します。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// 左上隅のx座標
+    pub x: u16,
+
+    /// 左上隅のy座標
+    pub y: u16,
+
+    /// 幅
+    pub width: u16,
+
+    /// 高さ
+    pub height: u16,
+}
+
+impl Rect {
+    /// `(x, y)`と`width`、`height`を指定して矩形を作成します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Rect;
+    /// let rect = Rect::new(10, 20, 30, 40);
+    /// 
+    /// assert_eq!(rect.x, 10);
+    /// assert_eq!(rect.height, 40);
+    /// ```
+    #[inline(always)]
+    pub const fn new(x: u16, y: u16, width: u16, height: u16) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    /// 幅または高さが0の場合`true`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Rect;
+    /// assert!(Rect::new(10, 10, 0, 5).is_empty());
+    /// assert!(!Rect::new(10, 10, 1, 1).is_empty());
+    /// ```
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// `(x, y)`が矩形に含まれる場合`true`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Rect;
+    /// let rect = Rect::new(10, 10, 5, 5);
+    /// 
+    /// assert!(rect.contains(14, 10));
+    /// assert!(!rect.contains(15, 10));
+    /// ```
+    #[inline(always)]
+    pub const fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.x && y >= self.y
+            && (x as u32) < self.x as u32 + self.width as u32
+            && (y as u32) < self.y as u32 + self.height as u32
+    }
+
+    /// 2つの矩形が重なる領域を返します。
+    /// 
+    /// 重ならない場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::Rect;
+    /// let a = Rect::new(0, 0, 10, 10);
+    /// let b = Rect::new(5, 5, 10, 10);
+    /// 
+    /// assert_eq!(a.intersection(&b), Some(Rect::new(5, 5, 5, 5)));
+    /// assert_eq!(a.intersection(&Rect::new(10, 0, 5, 5)), None);
+    /// ```
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x as u32 + self.width as u32).min(other.x as u32 + other.width as u32);
+        let bottom = (self.y as u32 + self.height as u32).min(other.y as u32 + other.height as u32);
+
+        if right <= left as u32 || bottom <= top as u32 {
+            return None;
+        }
+        Some(Rect::new(left, top, (right - left as u32) as u16, (bottom - top as u32) as u16))
+    }
+}
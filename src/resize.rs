@@ -0,0 +1,285 @@
+use alloc::vec::Vec;
+
+use crate::image::Image;
+use crate::generic::GenericImage;
+
+/// 拡大縮小に使う補間フィルターです。
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// 最近傍補間
+    /// 
+    /// 色を混ぜないため、ドット絵や透明色を持つスプライトに向いています。
+    Nearest,
+
+    /// 双線形補間
+    #[default]
+    Bilinear,
+
+    /// Catmull-Romスプラインによる双三次補間
+    Bicubic,
+
+    /// 3ローブのLanczos補間
+    /// 
+    /// 最も鮮明ですが、輪郭に若干のリンギングが出ます。
+    Lanczos3,
+}
+
+/// 出力の`len`個の位置に対応する入力の位置を返します。
+/// 
+/// 各ピクセルの中心で標本化します。
+fn nearest_indices(src_len: u16, len: u16) -> Vec<usize> {
+    let (src_len, len) = (src_len as usize, len as usize);
+    (0..len).map(|i| (2 * i + 1) * src_len / (2 * len)).collect()
+}
+
+/// 画像を最近傍補間で拡大縮小します。
+/// 
+/// [`GenericImage::resize_nearest`]の実装です。
+pub(crate) fn resize_nearest<S: GenericImage + ?Sized>(src: &S, width: u16, height: u16) -> Image {
+    if src.width() == 0 || src.height() == 0 {
+        return empty_source(src, width, height);
+    }
+
+    let xs = nearest_indices(src.width(), width);
+    let ys = nearest_indices(src.height(), height);
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for &y in &ys {
+        let row = src.row(y as u16);
+        pixels.extend(xs.iter().map(|&x| row[x]));
+    }
+
+    Image::from_parts(width, height, src.transparent_color(), pixels.into_boxed_slice())
+}
+
+/// 大きさが0の画像を拡大した結果として、透明色か黒で塗りつぶした画像を返します。
+fn empty_source<S: GenericImage + ?Sized>(src: &S, width: u16, height: u16) -> Image {
+    match src.transparent_color() {
+        Some(key) => {
+            let mut image = Image::with_transparent_color(width, height, key);
+            image.fill(key);
+            image
+        },
+        None => Image::new(width, height),
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) use self::filtered::resize;
+
+#[cfg(feature = "std")]
+impl Image {
+    /// `filter`で`width`x`height`に拡大縮小した新しい`Image`を返します。
+    /// 
+    /// [`GenericImage::resize`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Filter, Image, Pixel};
+    /// let mut image = Image::new(8, 8);
+    /// image.fill(Pixel::WHITE);
+    /// 
+    /// let thumbnail = image.resize(4, 4, Filter::Bilinear);
+    /// 
+    /// assert_eq!(thumbnail[(3, 3)], Pixel::WHITE);
+    /// ```
+    #[inline(always)]
+    pub fn resize(&self, width: u16, height: u16, filter: Filter) -> Image {
+        GenericImage::resize(self, width, height, filter)
+    }
+}
+
+#[cfg(feature = "std")]
+mod filtered {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::f32::consts::PI;
+
+    use super::{Filter, empty_source, resize_nearest};
+    use crate::image::Image;
+    use crate::pixel::{Pixel, key_transparent_pixels, unused_color};
+    use crate::generic::GenericImage;
+
+    /// 線形RGBと不透明度を乗算済みで保持する色
+    type Linear = [f32; 4];
+
+    impl Filter {
+        /// フィルターの半径を返します。
+        fn support(self) -> f32 {
+            match self {
+                Filter::Nearest => 0.5,
+                Filter::Bilinear => 1.0,
+                Filter::Bicubic => 2.0,
+                Filter::Lanczos3 => 3.0,
+            }
+        }
+
+        /// 中心からの距離`x`での重みを返します。
+        fn kernel(self, x: f32) -> f32 {
+            let x = x.abs();
+            match self {
+                Filter::Nearest => if x < 0.5 { 1.0 } else { 0.0 },
+                Filter::Bilinear => (1.0 - x).max(0.0),
+                Filter::Bicubic => {
+                    const A: f32 = -0.5;
+                    if x < 1.0 {
+                        ((A + 2.0) * x - (A + 3.0)) * x * x + 1.0
+                    } else if x < 2.0 {
+                        ((A * x - 5.0 * A) * x + 8.0 * A) * x - 4.0 * A
+                    } else {
+                        0.0
+                    }
+                },
+                Filter::Lanczos3 => {
+                    if x < f32::EPSILON {
+                        1.0
+                    } else if x < 3.0 {
+                        let px = PI * x;
+                        3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+                    } else {
+                        0.0
+                    }
+                },
+            }
+        }
+    }
+
+    /// 出力の1ピクセルに寄与する入力ピクセルの範囲と重み
+    struct Contribution {
+        start: usize,
+        weights: Vec<f32>,
+    }
+
+    /// `src_len`から`len`へ拡大縮小する際の各出力ピクセルの重みを計算します。
+    /// 
+    /// 縮小時はフィルターを広げてエイリアシングを抑えます。
+    fn contributions(src_len: usize, len: usize, filter: Filter) -> Vec<Contribution> {
+        let scale = src_len as f32 / len as f32;
+        let filter_scale = scale.max(1.0);
+        let support = filter.support() * filter_scale;
+
+        (0..len).map(|i| {
+            let center = (i as f32 + 0.5) * scale;
+            let start = ((center - support).floor().max(0.0)) as usize;
+            let end = ((center + support).ceil() as usize).min(src_len);
+
+            let mut weights: Vec<f32> = (start..end)
+                .map(|j| filter.kernel((j as f32 + 0.5 - center) / filter_scale))
+                .collect();
+
+            let sum: f32 = weights.iter().sum();
+            if sum.abs() > f32::EPSILON {
+                weights.iter_mut().for_each(|w| *w /= sum);
+                Contribution { start, weights }
+            } else {
+                // 重みがすべて0の場合は最も近いピクセルを使う
+                Contribution { start: (center as usize).min(src_len - 1), weights: vec![1.0] }
+            }
+        }).collect()
+    }
+
+    /// sRGBの8ビット値から線形RGBへの変換表を作成します。
+    fn linear_table() -> [f32; 256] {
+        core::array::from_fn(|v| {
+            let c = v as f32 / 255.0;
+            if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        })
+    }
+
+    /// 線形RGBの値を`max`段階のsRGBに量子化します。
+    fn to_srgb(linear: f32, max: f32) -> u16 {
+        let c = linear.clamp(0.0, 1.0);
+        let c = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+        (c * max).round() as u16
+    }
+
+    /// 画像を`filter`で拡大縮小します。
+    /// 
+    /// [`GenericImage::resize`]の実装です。
+    pub(crate) fn resize<S: GenericImage + ?Sized>(src: &S, width: u16, height: u16, filter: Filter) -> Image {
+        if filter == Filter::Nearest {
+            return resize_nearest(src, width, height);
+        }
+        if src.width() == 0 || src.height() == 0 {
+            return empty_source(src, width, height);
+        }
+
+        let (src_width, src_height) = (src.width() as usize, src.height() as usize);
+        let (width, height) = (width as usize, height as usize);
+        let key = src.transparent_color();
+        let table = linear_table();
+
+        // 横方向に縮小してから縦方向に縮小する
+        let columns = contributions(src_width, width, filter);
+        let mut horizontal: Vec<Linear> = Vec::with_capacity(width * src_height);
+        let mut line: Vec<Linear> = Vec::with_capacity(src_width);
+        for row in src.rows() {
+            line.clear();
+            line.extend(row.iter().map(|&pixel| {
+                if Some(pixel) == key {
+                    [0.0; 4]
+                } else {
+                    [table[pixel.r() as usize], table[pixel.g() as usize], table[pixel.b() as usize], 1.0]
+                }
+            }));
+
+            horizontal.extend(columns.iter().map(|c| {
+                let mut sum = [0.0; 4];
+                for (color, &weight) in line[c.start..].iter().zip(&c.weights) {
+                    for ch in 0..4 {
+                        sum[ch] += color[ch] * weight;
+                    }
+                }
+                sum
+            }));
+        }
+
+        let rows = contributions(src_height, height, filter);
+        let mut pixels = Vec::with_capacity(width * height);
+        let mut transparent = Vec::with_capacity(width * height);
+        for c in &rows {
+            for x in 0..width {
+                let mut sum = [0.0; 4];
+                for (j, &weight) in c.weights.iter().enumerate() {
+                    let color = &horizontal[(c.start + j) * width + x];
+                    for ch in 0..4 {
+                        sum[ch] += color[ch] * weight;
+                    }
+                }
+
+                // 不透明度が半分未満のピクセルは透明にする
+                if key.is_some() && sum[3] < 0.5 {
+                    pixels.push(Pixel::BLACK);
+                    transparent.push(true);
+                } else {
+                    let alpha = if key.is_some() { sum[3] } else { 1.0 };
+                    let r = to_srgb(sum[0] / alpha, 31.0);
+                    let g = to_srgb(sum[1] / alpha, 63.0);
+                    let b = to_srgb(sum[2] / alpha, 31.0);
+                    pixels.push(Pixel((r << 11) | (g << 5) | b));
+                    transparent.push(false);
+                }
+            }
+        }
+
+        let transparent_color = match key {
+            Some(key) => {
+                // 補間で透明色と同じ色が生じた場合は別の色を透明色にする
+                pixels.iter_mut().zip(&transparent).filter(|&(_, &t)| t).for_each(|(pixel, _)| *pixel = key);
+                match key_transparent_pixels(&mut pixels, |i| transparent[i]) {
+                    Ok(Some(key)) => Some(key),
+                    // 透明なピクセルがなくなった場合も、使われていなければ元の透明色を引き継ぐ
+                    Ok(None) if !pixels.contains(&key) => Some(key),
+                    Ok(None) => unused_color(pixels.iter().copied()),
+                    // 使われていない色がない場合は元の透明色を使うため、同じ色の不透明なピクセルも透明になる
+                    Err(_) => Some(key),
+                }
+            },
+            None => None,
+        };
+
+        Image::from_parts(width as u16, height as u16, transparent_color, pixels.into_boxed_slice())
+    }
+}
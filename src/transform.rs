@@ -0,0 +1,228 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::image::Image;
+use crate::pixel::Pixel;
+use crate::generic::{GenericImage, GenericImageMut};
+
+/// タイル処理の1辺のピクセル数
+/// 
+/// 1行分がキャッシュラインに収まる大きさです。
+const TILE: usize = 32;
+
+/// 幅と高さが入れ替わる変換の種類
+#[derive(Clone, Copy)]
+pub(crate) enum Remap {
+    /// 時計回りに90度回転
+    Rotate90,
+    /// 時計回りに270度回転
+    Rotate270,
+    /// 左上から右下への対角線で反転
+    Transpose,
+}
+
+/// `src`を`remap`で変換した新しい`Image`を返します。
+/// 
+/// 読み取りと書き込みの両方が局所的になるよう、`TILE`四方のタイル単位で処理します。
+pub(crate) fn remap<S: GenericImage + ?Sized>(src: &S, remap: Remap) -> Image {
+    let (width, height) = (src.width() as usize, src.height() as usize);
+    // 変換後の幅は元の高さになる
+    let mut pixels = vec![Pixel::BLACK; width * height];
+
+    for ty in (0..height).step_by(TILE) {
+        for tx in (0..width).step_by(TILE) {
+            for y in ty..(ty + TILE).min(height) {
+                let row = &src.row(y as u16)[tx..(tx + TILE).min(width)];
+                for (x, &pixel) in (tx..).zip(row) {
+                    let index = match remap {
+                        Remap::Rotate90 => x * height + (height - 1 - y),
+                        Remap::Rotate270 => (width - 1 - x) * height + y,
+                        Remap::Transpose => x * height + y,
+                    };
+                    pixels[index] = pixel;
+                }
+            }
+        }
+    }
+
+    Image::from_parts(height as u16, width as u16, src.transparent_color(), pixels.into_boxed_slice())
+}
+
+/// `n`x`n`の正方形のピクセルをその場で転置します。
+fn transpose_square(pixels: &mut [Pixel], n: usize) {
+    for ty in (0..n).step_by(TILE) {
+        for tx in (ty..n).step_by(TILE) {
+            for y in ty..(ty + TILE).min(n) {
+                // 対角タイルでは対角線より右側だけを入れ替える
+                let start = if tx == ty { y + 1 } else { tx };
+                for x in start..(tx + TILE).min(n) {
+                    pixels.swap(y * n + x, x * n + y);
+                }
+            }
+        }
+    }
+}
+
+/// 画像を左右反転します。
+/// 
+/// [`GenericImageMut::flip_horizontal`]の実装です。
+pub(crate) fn flip_horizontal<I: GenericImageMut + ?Sized>(image: &mut I) {
+    for y in 0..image.height() {
+        image.row_mut(y).reverse();
+    }
+}
+
+/// 画像を上下反転します。
+/// 
+/// [`GenericImageMut::flip_vertical`]の実装です。
+pub(crate) fn flip_vertical<I: GenericImageMut + ?Sized>(image: &mut I) {
+    let height = image.height();
+    let mut buf: Vec<Pixel> = Vec::with_capacity(image.width() as usize);
+
+    // 2行を同時に借用できないので1行分のバッファを経由して入れ替える
+    for y in 0..height / 2 {
+        buf.clear();
+        buf.extend_from_slice(image.row(y));
+        image.row_mut(height - 1 - y).swap_with_slice(&mut buf);
+        image.row_mut(y).copy_from_slice(&buf);
+    }
+}
+
+impl Image {
+    /// 画像をその場で左右反転します。
+    /// 
+    /// [`GenericImageMut::flip_horizontal`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::new(3, 1);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// image.flip_horizontal();
+    /// 
+    /// assert_eq!(image[(2, 0)], Pixel::RED);
+    /// ```
+    #[inline(always)]
+    pub fn flip_horizontal(&mut self) {
+        GenericImageMut::flip_horizontal(self);
+    }
+
+    /// 画像をその場で上下反転します。
+    /// 
+    /// [`GenericImageMut::flip_vertical`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::new(1, 3);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// image.flip_vertical();
+    /// 
+    /// assert_eq!(image[(0, 2)], Pixel::RED);
+    /// ```
+    #[inline(always)]
+    pub fn flip_vertical(&mut self) {
+        GenericImageMut::flip_vertical(self);
+    }
+
+    /// 画像をその場で180度回転します。
+    /// 
+    /// [`GenericImageMut::rotate180`]と同じですが、トレイトをインポートせずに使用できます。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// image.rotate180();
+    /// 
+    /// assert_eq!(image[(2, 1)], Pixel::RED);
+    /// ```
+    #[inline(always)]
+    pub fn rotate180(&mut self) {
+        GenericImageMut::rotate180(self);
+    }
+
+    /// 画像をその場で時計回りに90度回転します。
+    /// 
+    /// 幅と高さが入れ替わります。正方形の画像では新しいメモリを確保しません。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// image.rotate90();
+    /// 
+    /// assert_eq!((image.width(), image.height()), (2, 3));
+    /// assert_eq!(image[(1, 0)], Pixel::RED);
+    /// ```
+    pub fn rotate90(&mut self) {
+        if self.width() == self.height() {
+            let size = self.width() as usize;
+            transpose_square(self.pixels_mut(), size);
+            flip_horizontal(self);
+        } else {
+            *self = remap(self, Remap::Rotate90);
+        }
+    }
+
+    /// 画像をその場で時計回りに270度(反時計回りに90度)回転します。
+    /// 
+    /// 幅と高さが入れ替わります。正方形の画像では新しいメモリを確保しません。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(0, 0)] = Pixel::RED;
+    /// 
+    /// image.rotate270();
+    /// 
+    /// assert_eq!((image.width(), image.height()), (2, 3));
+    /// assert_eq!(image[(0, 2)], Pixel::RED);
+    /// ```
+    pub fn rotate270(&mut self) {
+        if self.width() == self.height() {
+            let size = self.width() as usize;
+            transpose_square(self.pixels_mut(), size);
+            GenericImageMut::flip_vertical(self);
+        } else {
+            *self = remap(self, Remap::Rotate270);
+        }
+    }
+
+    /// 画像をその場で転置します。
+    /// 
+    /// 左上から右下への対角線で反転し、幅と高さが入れ替わります。正方形の画像では新しいメモリを確保しません。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel};
+    /// let mut image = Image::new(3, 2);
+    /// image[(2, 1)] = Pixel::RED;
+    /// 
+    /// image.transpose();
+    /// 
+    /// assert_eq!((image.width(), image.height()), (2, 3));
+    /// assert_eq!(image[(1, 2)], Pixel::RED);
+    /// ```
+    pub fn transpose(&mut self) {
+        if self.width() == self.height() {
+            let size = self.width() as usize;
+            transpose_square(self.pixels_mut(), size);
+        } else {
+            *self = remap(self, Remap::Transpose);
+        }
+    }
+}
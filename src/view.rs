@@ -0,0 +1,457 @@
+use core::ops::{Index, IndexMut, Range};
+
+use crate::image::Image;
+use crate::image_buf::ImageBuf;
+use crate::pixel::Pixel;
+use crate::rect::Rect;
+use crate::generic::{GenericImage, GenericImageMut};
+
+/// 親画像の`stride`から`rect`の先頭ピクセルの位置とスライスの長さを返します。
+/// 
+/// `rect`が空の場合は、右下隅に置かれていても範囲外にならないよう`(0, 0)`を返します。
+#[inline(always)]
+const fn view_range(rect: &Rect, stride: usize) -> (usize, usize) {
+    if rect.width == 0 || rect.height == 0 {
+        return (0, 0);
+    }
+
+    let start = rect.y as usize * stride + rect.x as usize;
+    let len = (rect.height as usize - 1) * stride + rect.width as usize;
+    (start, len)
+}
+
+/// ビューのピクセルデータでの`y`行目の範囲を返します。
+/// 
+/// 幅が0のビューはピクセルデータが空なので、常に空の範囲を返します。
+#[inline(always)]
+fn row_range(rect: &Rect, stride: usize, y: u16) -> Range<usize> {
+    if rect.width == 0 {
+        return 0..0;
+    }
+
+    let start = y as usize * stride;
+    start..start + rect.width as usize
+}
+
+/// `rect`が`width`と`height`の範囲に収まる場合`true`を返します。
+#[inline(always)]
+const fn fits(rect: &Rect, width: u16, height: u16) -> bool {
+    rect.x as u32 + rect.width as u32 <= width as u32 && rect.y as u32 + rect.height as u32 <= height as u32
+}
+
+/// 画像の一部をコピーせずに参照するビューです。
+/// 
+/// [`Image::view`]で作成します。座標`(0, 0)`はビューの左上隅です。
+#[derive(Clone, Copy, Debug)]
+pub struct ImageView<'a> {
+    /// ビューの左上隅から始まるピクセルデータ
+    pixels: &'a [Pixel],
+
+    /// 親画像での位置と大きさ
+    rect: Rect,
+
+    /// 親画像の1行のピクセル数
+    stride: usize,
+
+    /// 透明色
+    transparent_color: Option<Pixel>,
+}
+
+/// 画像の一部をコピーせずに可変参照するビューです。
+/// 
+/// [`Image::view_mut`]で作成します。座標`(0, 0)`はビューの左上隅です。
+#[derive(Debug)]
+pub struct ImageViewMut<'a> {
+    /// ビューの左上隅から始まるピクセルデータ
+    pixels: &'a mut [Pixel],
+
+    /// 親画像での位置と大きさ
+    rect: Rect,
+
+    /// 親画像の1行のピクセル数
+    stride: usize,
+
+    /// 透明色
+    transparent_color: Option<Pixel>,
+}
+
+impl Image {
+    /// `rect`の範囲を参照するビューを返します。
+    /// 
+    /// `rect`が画像の範囲に収まらない場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Rect};
+    /// let mut image = Image::new(16, 16);
+    /// image[(4, 6)] = Pixel::WHITE;
+    /// 
+    /// let view = image.view(Rect::new(4, 4, 8, 8)).unwrap();
+    /// 
+    /// assert_eq!(view[(0, 2)], Pixel::WHITE);
+    /// assert!(image.view(Rect::new(12, 12, 8, 8)).is_none());
+    /// ```
+    pub fn view(&self, rect: Rect) -> Option<ImageView<'_>> {
+        if !fits(&rect, self.width(), self.height()) {
+            return None;
+        }
+
+        let stride = self.width() as usize;
+        let (start, len) = view_range(&rect, stride);
+        Some(ImageView {
+            pixels: &self.pixels()[start..start + len],
+            rect,
+            stride,
+            transparent_color: self.transparent_color(),
+        })
+    }
+
+    /// `rect`の範囲を可変参照するビューを返します。
+    /// 
+    /// `rect`が画像の範囲に収まらない場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Rect};
+    /// let mut image = Image::new(16, 16);
+    /// let mut view = image.view_mut(Rect::new(4, 4, 8, 8)).unwrap();
+    /// view[(0, 2)] = Pixel::WHITE;
+    /// 
+    /// assert_eq!(image[(4, 6)], Pixel::WHITE);
+    /// ```
+    pub fn view_mut(&mut self, rect: Rect) -> Option<ImageViewMut<'_>> {
+        if !fits(&rect, self.width(), self.height()) {
+            return None;
+        }
+
+        let stride = self.width() as usize;
+        let (start, len) = view_range(&rect, stride);
+        let transparent_color = self.transparent_color();
+        Some(ImageViewMut {
+            pixels: &mut self.pixels_mut()[start..start + len],
+            rect,
+            stride,
+            transparent_color,
+        })
+    }
+}
+
+impl<S: AsRef<[Pixel]>> ImageBuf<S> {
+    /// `rect`の範囲を参照するビューを返します。
+    /// 
+    /// `rect`が画像の範囲に収まらない場合は`None`を返します。
+    pub fn view(&self, rect: Rect) -> Option<ImageView<'_>> {
+        if !fits(&rect, self.width(), self.height()) {
+            return None;
+        }
+
+        let stride = self.width() as usize;
+        let (start, len) = view_range(&rect, stride);
+        Some(ImageView {
+            pixels: &self.pixels()[start..start + len],
+            rect,
+            stride,
+            transparent_color: self.transparent_color(),
+        })
+    }
+}
+
+impl<S: AsRef<[Pixel]> + AsMut<[Pixel]>> ImageBuf<S> {
+    /// `rect`の範囲を可変参照するビューを返します。
+    /// 
+    /// `rect`が画像の範囲に収まらない場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{GenericImageMut, ImageBuf, Pixel, Rect, Result};
+    /// # fn main() -> Result<()> {
+    /// let mut image = ImageBuf::new(4, 4, [Pixel::BLACK; 16])?;
+    /// image.view_mut(Rect::new(2, 2, 2, 2)).unwrap().fill(Pixel::WHITE);
+    /// 
+    /// assert_eq!(image[(1, 1)], Pixel::BLACK);
+    /// assert_eq!(image[(3, 3)], Pixel::WHITE);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn view_mut(&mut self, rect: Rect) -> Option<ImageViewMut<'_>> {
+        if !fits(&rect, self.width(), self.height()) {
+            return None;
+        }
+
+        let stride = self.width() as usize;
+        let (start, len) = view_range(&rect, stride);
+        let transparent_color = self.transparent_color();
+        Some(ImageViewMut {
+            pixels: &mut self.pixels_mut()[start..start + len],
+            rect,
+            stride,
+            transparent_color,
+        })
+    }
+}
+
+impl<'a> ImageView<'a> {
+    /// 親画像でのビューの左上隅のx座標を返します。
+    #[inline(always)]
+    pub fn x(&self) -> u16 {
+        self.rect.x
+    }
+
+    /// 親画像でのビューの左上隅のy座標を返します。
+    #[inline(always)]
+    pub fn y(&self) -> u16 {
+        self.rect.y
+    }
+
+    /// ビューの幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.rect.width
+    }
+
+    /// ビューの高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.rect.height
+    }
+
+    /// 親画像の1行のピクセル数を返します。
+    #[inline(always)]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    #[inline(always)]
+    pub fn transparent_color(&self) -> Option<Pixel> {
+        self.transparent_color
+    }
+
+    /// `(x, y)`の位置のピクセルの参照を取得します。
+    /// 
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Rect};
+    /// let image = Image::new(16, 16);
+    /// let view = image.view(Rect::new(4, 4, 8, 8)).unwrap();
+    /// 
+    /// assert_eq!(view.get_pixel(7, 7), Some(&Pixel::BLACK));
+    /// assert_eq!(view.get_pixel(8, 0), None);
+    /// ```
+    #[inline(always)]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<&'a Pixel> {
+        if x < self.rect.width && y < self.rect.height {
+            Some(&self.pixels[y as usize * self.stride + x as usize])
+        } else {
+            None
+        }
+    }
+
+    /// `rect`の範囲を参照するビューを返します。
+    /// 
+    /// `rect`はこのビューの左上隅を基準とした座標です。範囲に収まらない場合は`None`を返します。
+    pub fn view(&self, rect: Rect) -> Option<ImageView<'a>> {
+        if !fits(&rect, self.rect.width, self.rect.height) {
+            return None;
+        }
+
+        let (start, len) = view_range(&rect, self.stride);
+        Some(ImageView {
+            pixels: &self.pixels[start..start + len],
+            rect: Rect::new(self.rect.x + rect.x, self.rect.y + rect.y, rect.width, rect.height),
+            stride: self.stride,
+            transparent_color: self.transparent_color,
+        })
+    }
+}
+
+impl<'a> ImageViewMut<'a> {
+    /// 親画像でのビューの左上隅のx座標を返します。
+    #[inline(always)]
+    pub fn x(&self) -> u16 {
+        self.rect.x
+    }
+
+    /// 親画像でのビューの左上隅のy座標を返します。
+    #[inline(always)]
+    pub fn y(&self) -> u16 {
+        self.rect.y
+    }
+
+    /// ビューの幅を返します。
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.rect.width
+    }
+
+    /// ビューの高さを返します。
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.rect.height
+    }
+
+    /// 親画像の1行のピクセル数を返します。
+    #[inline(always)]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// 画像の透明色を返します。
+    /// 
+    /// 指定がない場合`None`になります。
+    #[inline(always)]
+    pub fn transparent_color(&self) -> Option<Pixel> {
+        self.transparent_color
+    }
+
+    /// `(x, y)`の位置のピクセルの参照を取得します。
+    /// 
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    #[inline(always)]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<&Pixel> {
+        if x < self.rect.width && y < self.rect.height {
+            Some(&self.pixels[y as usize * self.stride + x as usize])
+        } else {
+            None
+        }
+    }
+
+    /// `(x, y)`の位置のピクセルの可変参照を取得します。
+    /// 
+    /// `(x, y)`が`(width, height)`の範囲内にない場合は`None`を返します。
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use limg::{Image, Pixel, Rect};
+    /// let mut image = Image::new(16, 16);
+    /// let mut view = image.view_mut(Rect::new(4, 4, 8, 8)).unwrap();
+    /// *view.get_pixel_mut(1, 1).unwrap() = Pixel::WHITE;
+    /// 
+    /// assert_eq!(view.get_pixel_mut(8, 8), None);
+    /// assert_eq!(image[(5, 5)], Pixel::WHITE);
+    /// ```
+    #[inline(always)]
+    pub fn get_pixel_mut(&mut self, x: u16, y: u16) -> Option<&mut Pixel> {
+        if x < self.rect.width && y < self.rect.height {
+            Some(&mut self.pixels[y as usize * self.stride + x as usize])
+        } else {
+            None
+        }
+    }
+
+    /// 読み取り専用のビューを返します。
+    #[inline(always)]
+    pub fn as_view(&self) -> ImageView<'_> {
+        ImageView {
+            pixels: self.pixels,
+            rect: self.rect,
+            stride: self.stride,
+            transparent_color: self.transparent_color,
+        }
+    }
+
+    /// `rect`の範囲を可変参照するビューを返します。
+    /// 
+    /// `rect`はこのビューの左上隅を基準とした座標です。範囲に収まらない場合は`None`を返します。
+    pub fn view_mut(&mut self, rect: Rect) -> Option<ImageViewMut<'_>> {
+        if !fits(&rect, self.rect.width, self.rect.height) {
+            return None;
+        }
+
+        let (start, len) = view_range(&rect, self.stride);
+        Some(ImageViewMut {
+            pixels: &mut self.pixels[start..start + len],
+            rect: Rect::new(self.rect.x + rect.x, self.rect.y + rect.y, rect.width, rect.height),
+            stride: self.stride,
+            transparent_color: self.transparent_color,
+        })
+    }
+}
+
+impl Index<(u16, u16)> for ImageView<'_> {
+    type Output = Pixel;
+
+    #[inline(always)]
+    fn index(&self, index: (u16, u16)) -> &Self::Output {
+        self.get_pixel(index.0, index.1).expect("pixel index out of view bounds")
+    }
+}
+
+impl Index<(u16, u16)> for ImageViewMut<'_> {
+    type Output = Pixel;
+
+    #[inline(always)]
+    fn index(&self, index: (u16, u16)) -> &Self::Output {
+        self.get_pixel(index.0, index.1).expect("pixel index out of view bounds")
+    }
+}
+
+impl IndexMut<(u16, u16)> for ImageViewMut<'_> {
+    #[inline(always)]
+    fn index_mut(&mut self, index: (u16, u16)) -> &mut Self::Output {
+        self.get_pixel_mut(index.0, index.1).expect("pixel index out of view bounds")
+    }
+}
+
+impl GenericImage for ImageView<'_> {
+    #[inline(always)]
+    fn width(&self) -> u16 {
+        self.rect.width
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u16 {
+        self.rect.height
+    }
+
+    #[inline(always)]
+    fn transparent_color(&self) -> Option<Pixel> {
+        self.transparent_color
+    }
+
+    #[inline(always)]
+    fn row(&self, y: u16) -> &[Pixel] {
+        assert!(y < self.rect.height);
+        &self.pixels[row_range(&self.rect, self.stride, y)]
+    }
+}
+
+impl GenericImage for ImageViewMut<'_> {
+    #[inline(always)]
+    fn width(&self) -> u16 {
+        self.rect.width
+    }
+
+    #[inline(always)]
+    fn height(&self) -> u16 {
+        self.rect.height
+    }
+
+    #[inline(always)]
+    fn transparent_color(&self) -> Option<Pixel> {
+        self.transparent_color
+    }
+
+    #[inline(always)]
+    fn row(&self, y: u16) -> &[Pixel] {
+        assert!(y < self.rect.height);
+        &self.pixels[row_range(&self.rect, self.stride, y)]
+    }
+}
+
+impl GenericImageMut for ImageViewMut<'_> {
+    #[inline(always)]
+    fn row_mut(&mut self, y: u16) -> &mut [Pixel] {
+        assert!(y < self.rect.height);
+        &mut self.pixels[row_range(&self.rect, self.stride, y)]
+    }
+}
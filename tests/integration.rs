@@ -1,4 +1,4 @@
-use limg::{Image, Result};
+use limg::{draw, Animation, BitDepth, Compression, DecodeOptions, Dither, Error, Filter, Font, GenericImage, GenericImageMut, Image, ImageBuf, ImagePatch, ImageRef, IndexedImage, HeaderField, LimgDecoder, Pixel, PnmFormat, Rect, Result};
 use std::io::Cursor;
 use limg_core::decode_header;
 
@@ -47,4 +47,953 @@ fn from_reader_test() -> Result<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn decoder_rows_test() -> Result<()> {
+    let dir = std::fs::read_dir("tests/limg")?;
+
+    for item in dir.into_iter() {
+        let path = item?.path();
+        let image = Image::open(&path)?;
+
+        let mut decoder = LimgDecoder::new(std::fs::File::open(&path)?)?;
+        let mut rows = vec![Pixel::BLACK; decoder.width() as usize * 7];
+        let mut y = 0;
+
+        loop {
+            let read = decoder.read_rows(&mut rows)?;
+            if read == 0 {
+                break;
+            }
+
+            let start = y * image.width() as usize;
+            let len = read * image.width() as usize;
+            assert_eq!(&image.pixels()[start..start + len], &rows[..len]);
+            y += read;
+        }
+
+        assert_eq!(y, image.height() as usize);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bmp_24bit_bottom_up_test() -> Result<()> {
+    // 2x2の24ビットBMP（下の行から格納、行は4バイト境界にパディング）
+    let mut bmp = Vec::new();
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&70u32.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&54u32.to_le_bytes());
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&2i32.to_le_bytes());
+    bmp.extend_from_slice(&2i32.to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    bmp.extend_from_slice(&[0u8; 24]);
+    bmp.extend_from_slice(&[0, 0, 255, 0, 255, 0, 0, 0]);
+    bmp.extend_from_slice(&[255, 0, 0, 255, 255, 255, 0, 0]);
+
+    let image = Image::from_bmp(&bmp)?;
+
+    assert_eq!(image[(0, 0)], Pixel::BLUE);
+    assert_eq!(image[(1, 0)], Pixel::WHITE);
+    assert_eq!(image[(0, 1)], Pixel::RED);
+    assert_eq!(image[(1, 1)], Pixel::GREEN);
+
+    // ビットが連続していないマスクは拒否する
+    for masks in [[0x8000_0001u32, 0xFF00, 0xFF], [0xFF_0000, 0xF0F0, 0xFF], [0xFF_0000, 0xFF00, 0xFFFF_FFFF]] {
+        let mut bmp = Vec::new();
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&70u32.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&66u32.to_le_bytes());
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        bmp.extend_from_slice(&1i32.to_le_bytes());
+        bmp.extend_from_slice(&1i32.to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&32u16.to_le_bytes());
+        bmp.extend_from_slice(&3u32.to_le_bytes());
+        bmp.extend_from_slice(&[0u8; 20]);
+        masks.iter().for_each(|mask| bmp.extend_from_slice(&mask.to_le_bytes()));
+        bmp.extend_from_slice(&[0xFF; 4]);
+
+        let result = Image::from_bmp(&bmp);
+        if masks[2] == 0xFFFF_FFFF {
+            assert!(result.is_ok());
+        } else {
+            assert!(matches!(result, Err(Error::UnsupportedFormat)), "{:X?}", masks);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pnm_round_trip_test() -> Result<()> {
+    let image = Image::open("tests/limg/black_white_alpha_le.limg")?;
+
+    for format in [PnmFormat::Ppm, PnmFormat::PpmAscii, PnmFormat::Pam] {
+        let mut buf = vec![0u8; image.pnm_size(format)];
+        let size = image.to_pnm(&mut buf, format)?;
+        assert_eq!(size, buf.len());
+
+        let decoded = Image::from_pnm(&buf)?;
+        assert_eq!(decoded.pixels(), image.pixels());
+    }
+
+    // 16ビットのPGM
+    let mut pgm = b"P5 2 1 65535\n".to_vec();
+    pgm.extend_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+    let image = Image::from_pnm(&pgm)?;
+
+    assert_eq!(image.pixels(), &[Pixel::WHITE, Pixel::BLACK]);
+
+    // ヘッダーだけの巨大な画像は確保する前にエラーになる
+    assert!(Image::from_pnm(b"P6 65535 65535 255\n").is_err());
+    assert!(Image::from_pnm(b"P7\nWIDTH 65535\nHEIGHT 65535\nDEPTH 4\nMAXVAL 65535\nENDHDR\n").is_err());
+    assert!(Image::from_pnm(b"P3 65535 65535 255\n255 0 0\n").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn png_round_trip_test() -> Result<()> {
+    let encode = |width: u32, height: u32, color: png::ColorType, data: &[u8]| {
+        let mut buf = Vec::new();
+        let mut encoder = png::Encoder::new(&mut buf, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+        writer.finish().unwrap();
+        buf
+    };
+
+    for path in ["tests/limg/black_white_be.limg", "tests/limg/black_white_alpha_le.limg"] {
+        let image = Image::open(path)?;
+        let mut buf = Vec::new();
+        image.to_png(&mut buf)?;
+
+        let decoded = Image::from_png(buf.as_slice())?;
+        assert_eq!(decoded.pixels(), image.pixels(), "{}", path);
+        assert_eq!(decoded.transparent_color(), image.transparent_color(), "{}", path);
+    }
+
+    // 色の異なる透明なピクセルは使われていない1色にまとめられる
+    let data = [255, 0, 0, 255, 0, 0, 255, 0, 0, 255, 0, 0];
+    let image = Image::from_png(encode(3, 1, png::ColorType::Rgba, &data).as_slice())?;
+    assert_eq!(image.transparent_color(), Some(Pixel::MAGENTA));
+    assert_eq!(image.pixels(), &[Pixel::RED, Pixel::MAGENTA, Pixel::MAGENTA]);
+
+    // `u16`に収まらない大きさ
+    let wide = encode(u16::MAX as u32 + 1, 1, png::ColorType::Grayscale, &vec![0; u16::MAX as usize + 1]);
+    assert!(matches!(Image::from_png(wide.as_slice()), Err(Error::UnsupportedFormat)));
+
+    // 壊れたデータはPNGのエラーをそのまま返す
+    let mut corrupt = encode(1, 1, png::ColorType::Grayscale, &[0]);
+    corrupt[16] ^= 0xFF;
+    assert!(matches!(Image::from_png(corrupt.as_slice()), Err(Error::PngError(_))));
+
+    Ok(())
+}
+
+#[test]
+fn dither_preserves_mean_test() -> Result<()> {
+    // RGB565で表現できない灰色
+    let data = [100u8; 64 * 64 * 3];
+    let mean = |image: &Image| image.pixels().iter().map(|p| p.r() as f64).sum::<f64>() / image.pixels().len() as f64;
+
+    for dither in [Dither::FloydSteinberg, Dither::Atkinson, Dither::Bayer4x4, Dither::Bayer8x8] {
+        let image = Image::from_rgb888_dithered(64, 64, &data, dither)?;
+        assert!((mean(&image) - 100.0).abs() < 2.0, "{:?}", dither);
+    }
+
+    let image = Image::from_rgb888_dithered(64, 64, &data, Dither::None)?;
+    assert_eq!(mean(&image), 99.0);
+
+    Ok(())
+}
+
+#[test]
+fn dither_key_color_test() -> Result<()> {
+    // RGB565で表現できない透明色の縦線を右側に持つ灰色の画像
+    let key = [250, 3, 250];
+    let (width, stripe) = (32usize, 20usize);
+    let data: Vec<u8> = (0..width * 16).flat_map(|i| if i % width >= stripe { key } else { [100, 100, 100] }).collect();
+    let left: Vec<u8> = (0..stripe * 16).flat_map(|_| [100, 100, 100]).collect();
+
+    for dither in [Dither::FloydSteinberg, Dither::Atkinson, Dither::Bayer4x4] {
+        let image = Image::from_rgb888_dithered_with_transparent_color(width as u16, 16, &data, key, dither)?;
+        assert_eq!(image.transparent_color(), Some(Pixel::from_rgb(key)));
+
+        // 透明色はそのまま残る
+        for (x, y) in image.coordinates().filter(|&(x, _)| x as usize >= stripe) {
+            assert_eq!(image[(x, y)], Pixel::from_rgb(key));
+        }
+
+        // 透明色の誤差が周りに拡散されず、透明色のない画像と同じになる
+        let reference = Image::from_rgb888_dithered(stripe as u16, 16, &left, dither)?;
+        for (x, y) in reference.coordinates() {
+            assert_eq!(image[(x, y)], reference[(x, y)], "{dither:?} ({x}, {y})");
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn transform_test() {
+    for (width, height) in [(70, 45), (40, 40)] {
+        let mut image = Image::with_transparent_color(width, height, Pixel::MAGENTA);
+        for (x, y) in image.coordinates() {
+            image[(x, y)] = Pixel(x.wrapping_mul(31) ^ y.wrapping_mul(1021));
+        }
+
+        let rotated = image.rotated90();
+        assert_eq!((rotated.width(), rotated.height()), (height, width));
+        assert_eq!(rotated.transparent_color(), Some(Pixel::MAGENTA));
+        for (x, y) in image.coordinates() {
+            assert_eq!(rotated[(height - 1 - y, x)], image[(x, y)]);
+        }
+
+        let mut in_place = image.clone();
+        in_place.rotate90();
+        assert_eq!(in_place, rotated);
+        in_place.rotate90();
+        assert_eq!(in_place, image.rotated180());
+        in_place.rotate90();
+        assert_eq!(in_place, image.rotated270());
+        in_place.rotate90();
+        assert_eq!(in_place, image);
+
+        let mut transposed = image.transposed();
+        assert_eq!(transposed, image.rotated90().flipped_horizontal());
+        transposed.transpose();
+        assert_eq!(transposed, image);
+
+        let mut flipped = image.flipped_vertical();
+        flipped.flip_horizontal();
+        assert_eq!(flipped, image.rotated180());
+
+        let mut view_flipped = image.clone();
+        view_flipped.view_mut(Rect::new(0, 0, width, height)).unwrap().flip_vertical();
+        assert_eq!(view_flipped, image.flipped_vertical());
+    }
+}
+
+#[test]
+fn get_pixel_bounds_test() {
+    let mut image = Image::new(100, 50);
+
+    // 片方の座標だけが範囲外の場合も`None`になる
+    for (x, y) in [(100, 0), (0, 50), (99, 50), (100, 49), (100, 50), (u16::MAX, 0), (0, u16::MAX)] {
+        assert_eq!(image.get_pixel(x, y), None, "({}, {})", x, y);
+        assert!(image.get_pixel_mut(x, y).is_none(), "({}, {})", x, y);
+    }
+    assert!(image.get_pixel(99, 49).is_some());
+    assert!(image.get_pixel_mut(99, 49).is_some());
+}
+
+#[test]
+fn empty_view_test() -> Result<()> {
+    let mut image = Image::new(16, 16);
+    let mut buf = ImageBuf::new(16, 16, [Pixel::BLACK; 256])?;
+
+    // 右下隅や端に置かれた大きさ0の範囲も空のビューになる
+    for rect in [Rect::new(16, 16, 0, 0), Rect::new(16, 0, 0, 16), Rect::new(0, 16, 16, 0), Rect::new(3, 5, 0, 0)] {
+        assert_eq!(image.view(rect).unwrap().to_image().pixels().len(), 0);
+        assert_eq!(image.view_mut(rect).unwrap().width(), rect.width);
+        assert_eq!(buf.view(rect).unwrap().height(), rect.height);
+        assert!(buf.view_mut(rect).is_some());
+
+        let view = image.view(Rect::new(8, 8, 8, 8)).unwrap();
+        assert!(view.view(Rect::new(8, 8, 0, 0)).is_some());
+        let mut view = image.view_mut(Rect::new(8, 8, 8, 8)).unwrap();
+        assert!(view.view_mut(Rect::new(8, 8, 0, 0)).is_some());
+    }
+    assert!(image.view(Rect::new(17, 16, 0, 0)).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn blit_test() {
+    let mut src = Image::with_transparent_color(4, 4, Pixel::MAGENTA);
+    src.fill(Pixel::RED);
+    src[(0, 0)] = Pixel::MAGENTA;
+
+    // 一部だけ重なる位置
+    let mut dst = Image::new(4, 4);
+    dst.blit(&src, -2, 3);
+    assert_eq!(dst[(0, 3)], Pixel::RED);
+    assert_eq!(dst[(2, 3)], Pixel::BLACK);
+    assert_eq!(dst[(0, 2)], Pixel::BLACK);
+
+    // 範囲の端の位置は何も描画しない
+    let empty = Image::new(4, 4);
+    for (x, y) in [(i32::MIN, 0), (0, i32::MIN), (i32::MAX, 0), (0, i32::MAX), (i32::MIN, i32::MAX), (i32::MAX, i32::MIN)] {
+        let mut dst = Image::new(4, 4);
+        dst.blit(&src, x, y);
+        dst.blit_region(&src, Rect::new(1, 1, 3, 3), x, y);
+        assert_eq!(dst, empty);
+    }
+}
+
+#[test]
+fn resize_test() {
+    let mut image = Image::with_transparent_color(40, 30, Pixel::MAGENTA);
+    image.fill(Pixel::RED);
+    for (x, y) in image.coordinates() {
+        if x < 20 {
+            image[(x, y)] = Pixel::MAGENTA;
+        }
+    }
+
+    for filter in [Filter::Nearest, Filter::Bilinear, Filter::Bicubic, Filter::Lanczos3] {
+        for (width, height) in [(13, 7), (40, 30), (97, 64)] {
+            let resized = image.resize(width, height, filter);
+            let key = resized.transparent_color().unwrap();
+
+            assert_eq!((resized.width(), resized.height()), (width, height));
+            assert!(resized.pixels().iter().all(|&p| p == key || p == Pixel::RED), "{:?} {}x{}", filter, width, height);
+            assert_eq!(resized[(0, 0)], key);
+            assert_eq!(resized[(width - 1, height - 1)], Pixel::RED);
+        }
+    }
+
+    assert_eq!(image.resize_nearest(40, 30), image);
+
+    // 不透明なピクセルがすべての色を使う場合は、透明色に補間された不透明なピクセルも透明になる
+    let key = Pixel(0x0001);
+    let columns = 0x8000;
+    let colors: Vec<Pixel> = [0, 2].into_iter().chain(3..=u16::MAX).chain([0]).map(Pixel).collect();
+    let mut image = Image::with_transparent_color(columns + 1, 2, key);
+    for x in 0..columns {
+        image[(x, 0)] = colors[x as usize * 2];
+        image[(x, 1)] = colors[x as usize * 2 + 1];
+    }
+    image[(columns, 0)] = key;
+    image[(columns, 1)] = key;
+
+    let resized = image.resize(columns + 1, 3, Filter::Bilinear);
+    let mut used = vec![false; 0x10000];
+    for (x, y) in resized.coordinates().filter(|&(x, _)| x < columns) {
+        used[resized[(x, y)].0 as usize] = true;
+    }
+    assert!(used.iter().all(|&u| u));
+    assert_eq!(resized.transparent_color(), Some(key));
+    // 黒と`Pixel(0x0002)`の中間が透明色と同じ色になる
+    assert_eq!(resized[(0, 1)], key);
+    assert!((0..3).all(|y| resized[(columns, y)] == key));
+}
+
+#[test]
+fn draw_test() {
+    let mut polygon = Image::new(32, 32);
+    let mut rect = Image::new(32, 32);
+    draw::fill_polygon(&mut polygon, &[(-4, 3), (20, 3), (20, 40), (-4, 40)], Pixel::RED);
+    draw::fill_rect(&mut rect, Rect::new(0, 3, 20, 29), Pixel::RED);
+    assert_eq!(polygon, rect);
+
+    // 輪郭の内側だけが塗られ、外側には漏れない
+    let mut image = Image::new(32, 32);
+    draw::circle(&mut image, 16, 16, 10, Pixel::WHITE);
+    draw::flood_fill(&mut image, 16, 16, Pixel::RED);
+    let mut filled = Image::new(32, 32);
+    draw::fill_circle(&mut filled, 16, 16, 10, Pixel::RED);
+    draw::circle(&mut filled, 16, 16, 10, Pixel::WHITE);
+    assert_eq!(image, filled);
+
+    // 範囲外の座標でもパニックしない
+    let mut image = Image::new(16, 16);
+    let mut view = image.view_mut(Rect::new(4, 4, 8, 8)).unwrap();
+    draw::line(&mut view, -100_000, -3, 100_000, 3, Pixel::WHITE);
+    draw::line_aa(&mut view, 3, -50, 5, 50, Pixel::WHITE);
+    draw::thick_line(&mut view, -20, -20, 30, 30, 4, Pixel::WHITE);
+    draw::fill_ellipse(&mut view, 4, 4, 500, 2, Pixel::WHITE);
+    draw::flood_fill(&mut view, 100, 0, Pixel::RED);
+    assert!((0..16).all(|i| image[(i, 0)] == Pixel::BLACK && image[(0, i)] == Pixel::BLACK));
+    assert_eq!(image[(8, 8)], Pixel::WHITE);
+
+    // 切り取った線は大きな画像に描画した線の一部と一致する
+    for (x0, y0, x1, y1) in [(-30, -7, 50, 20), (45, -20, -12, 30), (3, 60, 5, -40), (-25, 4, 60, 4), (6, -3, 6, 70)] {
+        let mut small = Image::new(8, 8);
+        let mut large = Image::new(128, 128);
+        draw::line(&mut small, x0, y0, x1, y1, Pixel::WHITE);
+        draw::line(&mut large, x0 + 40, y0 + 40, x1 + 40, y1 + 40, Pixel::WHITE);
+        assert_eq!(small, large.view(Rect::new(40, 40, 8, 8)).unwrap().to_image(), "({}, {}) - ({}, {})", x0, y0, x1, y1);
+    }
+
+    // 座標が`i32`の範囲の端でも溢れず、画像の範囲だけを処理する
+    let mut image = Image::new(4, 4);
+    draw::line(&mut image, i32::MIN, i32::MIN, i32::MAX, i32::MAX, Pixel::WHITE);
+    assert!((0..4).all(|i| image[(i, i)] == Pixel::WHITE));
+    assert_eq!(image[(1, 0)], Pixel::BLACK);
+
+    let mut image = Image::new(4, 4);
+    draw::line(&mut image, i32::MAX, 2, i32::MIN, 2, Pixel::WHITE);
+    draw::line(&mut image, -2_000_000_000, 0, 2_000_000_000, 1, Pixel::WHITE);
+    assert!((0..4).all(|i| image[(i, 2)] == Pixel::WHITE && image[(i, 1)] == Pixel::WHITE));
+    assert!((0..4).all(|i| image[(i, 0)] == Pixel::BLACK && image[(i, 3)] == Pixel::BLACK));
+
+    let mut image = Image::new(4, 4);
+    draw::line_aa(&mut image, -2_000_000_000, 0, 2_000_000_000, 1, Pixel::WHITE);
+    draw::line_aa(&mut image, i32::MIN, i32::MAX, i32::MAX, i32::MIN, Pixel::WHITE);
+    draw::line_aa(&mut image, 1, i32::MIN, 1, i32::MAX, Pixel::WHITE);
+    assert_eq!(image[(0, 0)], Pixel::WHITE);
+    assert_eq!(image[(1, 3)], Pixel::WHITE);
+    assert_eq!(image[(3, 3)], Pixel::BLACK);
+
+    let mut image = Image::new(4, 4);
+    draw::thick_line(&mut image, i32::MIN, 0, i32::MAX, 0, 3, Pixel::WHITE);
+    draw::thick_line(&mut image, i32::MIN, i32::MIN, i32::MAX, i32::MAX, 2, Pixel::WHITE);
+    assert!((0..4).all(|i| image[(i, 0)] == Pixel::WHITE && image[(i, i)] == Pixel::WHITE));
+    assert_eq!(image[(0, 3)], Pixel::BLACK);
+
+    let mut image = Image::new(4, 4);
+    for (cx, cy) in [(i32::MAX, 0), (i32::MIN, 0), (0, i32::MAX), (0, i32::MIN), (i32::MAX, i32::MIN)] {
+        draw::circle(&mut image, cx, cy, 5, Pixel::WHITE);
+        draw::fill_circle(&mut image, cx, cy, u16::MAX, Pixel::WHITE);
+        draw::ellipse(&mut image, cx, cy, u16::MAX, 3, Pixel::WHITE);
+        draw::fill_ellipse(&mut image, cx, cy, 3, 3, Pixel::WHITE);
+    }
+    assert!(image.pixels().iter().all(|&p| p == Pixel::BLACK));
+    draw::fill_ellipse(&mut image, 1, 1, u16::MAX, u16::MAX, Pixel::WHITE);
+    assert!(image.pixels().iter().all(|&p| p == Pixel::WHITE));
+
+    // 一部だけ重なる円は大きな画像に描画した円の一部と一致する
+    let mut small = Image::new(8, 8);
+    let mut large = Image::new(128, 128);
+    draw::circle(&mut small, -20, 3, 24, Pixel::WHITE);
+    draw::fill_ellipse(&mut small, 10, -5, 6, 9, Pixel::RED);
+    draw::circle(&mut large, 20, 43, 24, Pixel::WHITE);
+    draw::fill_ellipse(&mut large, 50, 35, 6, 9, Pixel::RED);
+    assert_eq!(small, large.view(Rect::new(40, 40, 8, 8)).unwrap().to_image());
+
+    let mut image = Image::new(4, 4);
+    draw::fill_polygon(&mut image, &[(i32::MIN, i32::MIN), (i32::MAX, i32::MIN), (i32::MAX, i32::MAX), (i32::MIN, i32::MAX)], Pixel::WHITE);
+    assert!(image.pixels().iter().all(|&p| p == Pixel::WHITE));
+    let mut image = Image::new(4, 4);
+    draw::fill_polygon(&mut image, &[(i32::MIN, i32::MIN), (i32::MAX, i32::MAX), (i32::MIN, i32::MAX)], Pixel::WHITE);
+    assert_eq!(image[(0, 3)], Pixel::WHITE);
+    assert_eq!(image[(3, 0)], Pixel::BLACK);
+}
+
+#[test]
+fn text_test() {
+    let font = Font::BUILTIN_5X7;
+    let text = "The quick brown fox jumps over the lazy dog 0123456789";
+
+    for width in [1, 5, 17, 40, 100, 400] {
+        let (text_width, text_height) = font.measure_wrapped(text, width);
+        let mut image = Image::new(420, 400);
+        image.draw_text_wrapped(Rect::new(0, 0, width, 400), text, &font, Pixel::WHITE);
+
+        // 描画されたピクセルはすべて測定した範囲に収まる
+        for (x, y) in image.coordinates() {
+            if image[(x, y)] == Pixel::WHITE {
+                assert!((x as u32) < text_width.max(5) && (y as u32) < text_height, "{} ({}, {})", width, x, y);
+            }
+        }
+    }
+
+    let mut clipped = Image::new(20, 10);
+    clipped.draw_text(-3, -100, "x\n".repeat(20).as_str(), &font, Pixel::WHITE);
+    clipped.draw_text_wrapped(Rect::new(15, 5, 40, 40), "overflow", &font, Pixel::WHITE);
+
+    // 座標が`i32`の範囲の端でも溢れない
+    let mut edge = Image::new(20, 10);
+    for (x, y) in [(i32::MAX - 2, i32::MAX - 2), (i32::MIN, i32::MIN), (i32::MAX - 2, 0), (0, i32::MAX - 2), (i32::MIN + 1, 0)] {
+        edge.draw_text(x, y, "edge\ncase", &font, Pixel::WHITE);
+    }
+    assert!(edge.pixels().iter().all(|&p| p == Pixel::BLACK));
+    edge.draw_text(-6, -2, "ab", &font, Pixel::WHITE);
+    assert!(edge.pixels().contains(&Pixel::WHITE));
+    assert_eq!(font.measure("ab\n\nc"), (11, 23));
+}
+
+#[cfg(feature = "embedded-graphics")]
+#[test]
+fn embedded_graphics_test() {
+    use embedded_graphics_core::draw_target::DrawTarget;
+    use embedded_graphics_core::geometry::{Point, Size};
+    use embedded_graphics_core::image::ImageDrawable;
+    use embedded_graphics_core::pixelcolor::Rgb565;
+    use embedded_graphics_core::primitives::Rectangle;
+
+    let mut sheet = Image::new(8, 8);
+    for (x, y) in sheet.coordinates() {
+        sheet[(x, y)] = Pixel(y * 8 + x);
+    }
+
+    // はみ出した領域は切り取られ、色の並びはずれない
+    let mut image = Image::new(8, 8);
+    let area = Rectangle::new(Point::new(-2, -3), Size::new(8, 8));
+    let colors = (0..64i32).map(|i| Rgb565::from(Pixel(((i / 8 - 3) * 8 + i % 8 - 2) as u16)));
+    image.fill_contiguous(&area, colors).unwrap();
+    assert_eq!(image[(0, 0)], sheet[(0, 0)]);
+    assert_eq!(image[(5, 4)], sheet[(5, 4)]);
+    assert_eq!(image[(6, 5)], Pixel::BLACK);
+
+    let view = sheet.view(Rect::new(2, 2, 4, 4)).unwrap();
+    let mut display = Image::new(4, 4);
+    view.draw_sub_image(&mut display, &Rectangle::new(Point::new(1, 1), Size::new(2, 2))).unwrap();
+    assert_eq!(display[(0, 0)], sheet[(3, 3)]);
+    assert_eq!(display[(1, 1)], sheet[(4, 4)]);
+    assert_eq!(display[(2, 2)], Pixel::BLACK);
+}
+
+static RED_LE: ImageRef = limg::include_limg!("limg/red_le.limg");
+static BLACK_WHITE_ALPHA_BE: ImageRef = limg::include_limg!("limg/black_white_alpha_be.limg");
+
+#[test]
+fn image_ref_test() -> Result<()> {
+    let dir = std::fs::read_dir("tests/limg")?;
+
+    for item in dir.into_iter() {
+        let path = item?.path();
+        let data = std::fs::read(&path)?;
+
+        let image = Image::open(&path)?;
+        let image_ref = ImageRef::new(&data)?;
+
+        assert_eq!(image_ref.to_image(), image);
+        assert!(image_ref.pixels().eq(image.pixels().iter().copied()));
+        for (row, expected) in image_ref.rows().zip(image.rows()) {
+            assert!(row.eq(expected.iter().copied()));
+        }
+
+        assert!(ImageRef::new(&data[..data.len() - 1]).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn include_limg_test() -> Result<()> {
+    const WIDTH: u16 = RED_LE.width();
+    const KEY: Option<Pixel> = BLACK_WHITE_ALPHA_BE.transparent_color();
+
+    let red = Image::open("tests/limg/red_le.limg")?;
+    assert_eq!(WIDTH, red.width());
+    assert_eq!(RED_LE.to_image(), red);
+
+    let alpha = Image::open("tests/limg/black_white_alpha_be.limg")?;
+    assert_eq!(KEY, alpha.transparent_color());
+    assert_eq!(BLACK_WHITE_ALPHA_BE.to_image(), alpha);
+
+    Ok(())
+}
+
+#[test]
+fn image_buf_test() -> Result<()> {
+    let dir = std::fs::read_dir("tests/limg")?;
+    let mut storage = vec![Pixel::BLACK; 300 * 300];
+
+    for item in dir.into_iter() {
+        let path = item?.path();
+        let data = std::fs::read(&path)?;
+        let image = Image::open(&path)?;
+
+        let image_buf = ImageBuf::from_buffer(&data, &mut storage[..])?;
+        assert_eq!(image_buf.pixels(), image.pixels());
+        assert_eq!(image_buf.transparent_color(), image.transparent_color());
+        assert_eq!(image_buf.to_image(), image);
+
+        let mut buf = vec![0u8; data.len()];
+        image_buf.to_buffer_with_endian(&mut buf, ImageRef::new(&data)?.pixel_endian())?;
+        assert_eq!(buf, data);
+    }
+
+    assert!(ImageBuf::from_buffer(std::fs::read("tests/limg/red_le.limg")?, [Pixel::BLACK; 16]).is_err());
+
+    let mut image = ImageBuf::with_transparent_color(8, 8, Pixel::MAGENTA, [Pixel::BLACK; 64])?;
+    draw::fill_rect(&mut image, Rect::new(2, 2, 4, 4), Pixel::RED);
+    image.flip_horizontal();
+    image.blit(&Image::with_transparent_color(2, 2, Pixel::MAGENTA), 0, 0);
+    assert_eq!(image[(6, 6)], Pixel::BLACK);
+    assert_eq!(image[(5, 5)], Pixel::RED);
+    assert_eq!(image.view(Rect::new(2, 2, 4, 4)).unwrap().to_image().pixels(), &[Pixel::RED; 16]);
+
+    Ok(())
+}
+
+#[test]
+fn animation_test() -> Result<()> {
+    let mut animation = Animation::with_transparent_color(300, 300, Pixel::new(0xFFFF));
+    animation.set_loop_count(3);
+    for (i, name) in ["black_white_alpha_le", "black_white_alpha_be"].iter().enumerate() {
+        let image = Image::open(format!("tests/limg/{}.limg", name))?;
+        animation.push_frame(image, 100 * (i as u16 + 1))?;
+    }
+    assert!(animation.push_frame(Image::open("tests/limg/red_le.limg")?, 100).is_err());
+
+    let path = std::env::temp_dir().join("limg_animation_test.lima");
+    animation.save_with_endian(&path, limg::PixelEndian::Big)?;
+    let decoded = Animation::open(&path)?;
+    std::fs::remove_file(&path)?;
+
+    assert_eq!(decoded, animation);
+    assert_eq!(decoded.loop_count(), 3);
+    assert_eq!(decoded.frames().map(|frame| frame.duration()).collect::<Vec<_>>(), vec![100, 200]);
+    assert_eq!(decoded.frame_index_at(250), Some(1));
+    assert_eq!(decoded.frame_index_at(900), Some(1));
+
+    let mut buf = vec![0u8; animation.encoded_size()];
+    animation.to_buffer(&mut buf)?;
+    assert!(Animation::from_buffer(&buf[..buf.len() - 1]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn patch_test() -> Result<()> {
+    let old = Image::open("tests/limg/black_white_be.limg")?;
+    let mut new = old.clone();
+    draw::fill_rect(&mut new, Rect::new(10, 20, 30, 5), Pixel::RED);
+    draw::line(&mut new, 200, 200, 250, 260, Pixel::GREEN);
+    new[(299, 0)] = Pixel::BLUE;
+
+    let rects = old.diff(&new);
+    for (x, y) in new.coordinates() {
+        if old[(x, y)] != new[(x, y)] {
+            assert!(rects.iter().any(|r| r.contains(x, y)));
+        }
+    }
+    assert!(old.diff_with_threshold(&new, 0).len() >= rects.len());
+
+    // 右上の点は左と下の点を結合した矩形にだけ近い
+    let blank = Image::new(12, 12);
+    let mut dots = blank.clone();
+    for (x, y) in [(10, 2), (5, 4), (10, 7), (8, 10)] {
+        dots[(x, y)] = Pixel::RED;
+    }
+    assert_eq!(blank.diff_with_threshold(&dots, 2), vec![Rect::new(5, 2, 6, 9)]);
+
+    let patch = ImagePatch::new(&old, &new);
+    let mut buf = vec![0u8; patch.encoded_size()];
+    patch.to_buffer_with_endian(&mut buf, limg::PixelEndian::Big)?;
+    let decoded = ImagePatch::from_buffer(&buf)?;
+    assert_eq!(decoded, patch);
+    assert!(ImagePatch::from_buffer(&buf[..buf.len() - 1]).is_err());
+
+    let mut display = old.clone();
+    display.apply_patch(&decoded)?;
+    assert_eq!(display, new);
+
+    let mut small = Image::new(10, 10);
+    assert!(small.apply_patch(&decoded).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn compression_test() -> Result<()> {
+    let mut images = Vec::new();
+    for item in std::fs::read_dir("tests/limg")? {
+        images.push(Image::open(item?.path())?);
+    }
+
+    // 圧縮しにくいノイズと繰り返し模様
+    let mut seed = 12345u32;
+    let mut noise = Image::with_transparent_color(97, 61, Pixel::MAGENTA);
+    for (x, y) in noise.coordinates() {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        noise[(x, y)] = if x % 13 < 6 { Pixel::new(x / 13 * 977) } else { Pixel::new((seed >> 16) as u16) };
+    }
+    images.push(noise);
+
+    for image in &images {
+        for compression in [Compression::Rle16, Compression::Lz4Like] {
+            for endian in [limg::PixelEndian::Little, limg::PixelEndian::Big] {
+                let buf = image.to_compressed_with_endian(compression, endian)?;
+                assert_eq!(&Image::from_buffer(&buf)?, image);
+                assert_eq!(&Image::from_read(buf.as_slice())?, image);
+                assert!(ImageRef::new(&buf).is_err());
+                assert!(Image::from_buffer(&buf[..buf.len() - 1]).is_err());
+
+                // 途中で終わる圧縮データはバッファからでも`LimgDecoder`からでも同じエラーになる
+                let short = &buf[..buf.len() - 3];
+                let expected = (buf.len(), short.len());
+                assert!(matches!(Image::from_buffer(short), Err(Error::Truncated { expected: e, actual: a }) if (e, a) == expected));
+                assert!(matches!(
+                    LimgDecoder::new(short).and_then(|decoder| decoder.read_image()),
+                    Err(Error::Truncated { expected: e, actual: a }) if (e, a) == expected
+                ));
+
+                let mut decoder = LimgDecoder::new(buf.as_slice())?;
+                assert_eq!(decoder.compression(), Some(compression));
+                let mut rows = vec![Pixel::BLACK; image.width() as usize * 7];
+                let mut y = 0;
+                loop {
+                    let read = decoder.read_rows(&mut rows)?;
+                    if read == 0 {
+                        break;
+                    }
+                    let start = y * image.width() as usize;
+                    let len = read * image.width() as usize;
+                    assert_eq!(&image.pixels()[start..start + len], &rows[..len]);
+                    y += read;
+                }
+
+                let mut storage = vec![Pixel::BLACK; image.pixels().len()];
+                let image_buf = ImageBuf::from_buffer(&buf, &mut storage[..])?;
+                assert_eq!(image_buf.pixels(), image.pixels());
+                assert_eq!(image_buf.transparent_color(), image.transparent_color());
+            }
+        }
+    }
+
+    // 圧縮データの長さだけが大きいヘッダーでも、その長さを確保せずにエラーになる
+    let mut hostile = images[0].to_compressed(Compression::Lz4Like)?[..16].to_vec();
+    hostile[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+    match Image::from_read(hostile.as_slice()) {
+        Err(Error::Truncated { expected, actual }) => assert_eq!((expected, actual), (16 + u32::MAX as usize, 16)),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let flat = Image::open("tests/limg/red_le.limg")?;
+    assert!(flat.to_compressed(Compression::Rle16)?.len() < 3000);
+    assert!(flat.to_compressed(Compression::Lz4Like)?.len() < 3000);
+
+    Ok(())
+}
+
+#[test]
+fn indexed_test() -> Result<()> {
+    // 透明色と3色のアイコン
+    let mut icon = Image::with_transparent_color(20, 20, Pixel::MAGENTA);
+    icon.fill(Pixel::MAGENTA);
+    draw::fill_circle(&mut icon, 10, 10, 8, Pixel::YELLOW);
+    draw::circle(&mut icon, 10, 10, 8, Pixel::BLACK);
+    draw::fill_rect(&mut icon, Rect::new(7, 7, 2, 2), Pixel::BLACK);
+    draw::line(&mut icon, 6, 13, 14, 13, Pixel::RED);
+
+    let indexed = icon.quantize(16);
+    assert_eq!(indexed.palette().len(), 4);
+    assert_eq!(indexed.bit_depth(), BitDepth::Two);
+    assert_eq!(indexed.transparent_color(), Some(Pixel::MAGENTA));
+    assert_eq!(indexed.to_image(), icon);
+
+    let path = std::env::temp_dir().join("limg_indexed_test.limi");
+    indexed.save(&path)?;
+    let decoded = IndexedImage::open(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(decoded, indexed);
+    assert!(indexed.encoded_size() * 6 < 12 + 20 * 20 * 2);
+
+    // グラデーションは指定した色数以下に減色される
+    let mut gradient = Image::new(64, 64);
+    for (x, y) in gradient.coordinates() {
+        gradient[(x, y)] = Pixel::from_rgb([(x * 4) as u8, (y * 4) as u8, 128]);
+    }
+    for max_colors in [2, 4, 16, 256] {
+        let indexed = gradient.quantize(max_colors);
+        assert!(indexed.palette().len() <= max_colors);
+        assert_eq!(indexed.transparent_color(), None);
+
+        let mut buf = vec![0u8; indexed.encoded_size()];
+        indexed.to_buffer(&mut buf)?;
+        assert_eq!(IndexedImage::from_buffer(&buf)?, indexed);
+        assert!(IndexedImage::from_buffer(&buf[..buf.len() - 1]).is_err());
+    }
+
+    // 減色した色が近いことを確認する
+    let quantized = gradient.quantize(256).to_image();
+    for (a, b) in gradient.pixels().iter().zip(quantized.pixels()) {
+        let (a, b) = (a.into_rgb(), b.into_rgb());
+        assert!((0..3).all(|ch| a[ch].abs_diff(b[ch]) <= 24));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn cli_test() -> Result<()> {
+    use std::process::Command;
+
+    let dir = std::env::temp_dir().join("limg_cli_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir)?;
+    for name in ["red_le.limg", "blue_be.limg", "black_white_alpha_le.limg"] {
+        std::fs::copy(format!("tests/limg/{name}"), dir.join(name))?;
+    }
+    let limg = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_limg")).current_dir(&dir).args(args).output().unwrap();
+
+    let output = limg(&["info", "*.limg"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.contains("blue_be.limg: width=300 height=300 endian=big key=none compression=none"));
+    assert!(stdout.contains("key=0xFFFF"));
+
+    assert!(limg(&["convert", "--to", "png", "--out-dir", "png", "*.limg"]).status.success());
+    assert!(limg(&["convert", "-o", "blue.limg", "--compress", "rle16", "png/blue_be.png"]).status.success());
+    assert_eq!(Image::open(dir.join("blue.limg"))?, Image::open(dir.join("blue_be.limg"))?);
+
+    assert!(limg(&["swap-endian", "red_le.limg"]).status.success());
+    assert!(limg(&["set-key", "0xF800", "red_le.limg"]).status.success());
+    assert!(limg(&["resize", "30x20", "--filter", "nearest", "-o", "small.limg", "red_le.limg"]).status.success());
+    assert!(limg(&["crop", "5,5,10,4", "-o", "crop.limg", "small.limg"]).status.success());
+    let stdout = String::from_utf8_lossy(&limg(&["info", "crop.limg"]).stdout).into_owned();
+    assert_eq!(stdout.trim(), "crop.limg: width=10 height=4 endian=big key=0xF800 compression=none");
+
+    std::fs::write(dir.join("broken.limg"), &std::fs::read(dir.join("blue_be.limg"))?[..100])?;
+    assert_eq!(limg(&["validate", "*.limg"]).status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&limg(&["info", "broken.limg"]).stdout).ends_with("compression=none truncated\n"));
+    assert_eq!(limg(&["crop", "0,0,400,1", "crop.limg"]).status.code(), Some(1));
+    assert_eq!(limg(&["resize", "big", "crop.limg"]).status.code(), Some(2));
+    assert_eq!(limg(&["info", "missing*.limg"]).status.code(), Some(2));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn probe_test() -> Result<()> {
+    let info = limg::probe("tests/limg/black_white_alpha_be.limg")?;
+    assert_eq!((info.width(), info.height()), (300, 300));
+    assert_eq!(info.transparent_color(), Some(Pixel::WHITE));
+    assert!(matches!(info.pixel_endian(), limg::PixelEndian::Big));
+    assert_eq!(info.compression(), None);
+    assert_eq!(info.encoded_size(), 12 + 300 * 300 * 2);
+    assert_eq!(info.file_size(), Some(info.encoded_size() as u64));
+    assert!(!info.is_truncated() && !info.has_trailing_bytes());
+
+    let buf = std::fs::read("tests/limg/red_le.limg")?;
+    let info = limg::probe_read(&buf[..12])?;
+    assert_eq!(info.file_size(), None);
+    assert!(!info.is_truncated());
+    assert!(limg::probe_buffer(&buf[..buf.len() - 1])?.is_truncated());
+    let mut trailing = buf.clone();
+    trailing.push(0);
+    assert!(limg::probe_buffer(&trailing)?.has_trailing_bytes());
+    assert!(limg::probe_buffer(&buf[..8]).is_err());
+
+    let compressed = Image::from_buffer(&buf)?.to_compressed(Compression::Lz4Like)?;
+    let info = limg::probe_read(compressed.as_slice())?;
+    assert_eq!(info.compression(), Some(Compression::Lz4Like));
+    assert_eq!(info.encoded_size(), compressed.len());
+    assert!(!limg::probe_buffer(&compressed)?.is_truncated());
+
+    Ok(())
+}
+
+#[test]
+fn decode_options_test() -> Result<()> {
+    let buf = std::fs::read("tests/limg/black_white_le.limg")?;
+    let image = Image::from_buffer(&buf)?;
+
+    // ヘッダーの不正なフィールドと位置
+    let mut broken = buf.clone();
+    broken[0] = b'X';
+    assert!(matches!(Image::from_buffer(&broken), Err(Error::InvalidHeader { field: HeaderField::Signature, offset: 0 })));
+    broken = buf.clone();
+    broken[5] |= 0x80;
+    assert!(matches!(Image::from_buffer(&broken), Err(Error::InvalidHeader { field: HeaderField::Flags, offset: 5 })));
+    broken = buf.clone();
+    broken[6..8].fill(0);
+    assert!(matches!(Image::from_buffer(&broken), Err(Error::InvalidHeader { field: HeaderField::Width, offset: 6 })));
+    assert!(matches!(ImageRef::new(&broken), Err(Error::InvalidHeader { field: HeaderField::Width, offset: 6 })));
+    assert!(matches!(limg::probe_buffer(&broken), Err(Error::InvalidHeader { field: HeaderField::Width, offset: 6 })));
+    broken = buf.clone();
+    broken[8..10].fill(0);
+    assert!(matches!(Image::from_read(broken.as_slice()), Err(Error::InvalidHeader { field: HeaderField::Height, offset: 8 })));
+    let mut compressed = image.to_compressed(Compression::Lz4Like)?;
+    compressed[6..8].fill(0);
+    assert!(matches!(Image::from_buffer(&compressed), Err(Error::InvalidHeader { field: HeaderField::Width, offset: 6 })));
+    assert!(matches!(ImageRef::new(&buf[..7]), Err(Error::Truncated { expected: 12, actual: 7 })));
+    assert!(matches!(Image::from_read(&buf[..7]), Err(Error::Truncated { expected: 12, actual: 7 })));
+
+    // 不足したデータ
+    let truncated = &buf[..12 + 300 * 2 * 100 + 1];
+    match Image::from_buffer(truncated) {
+        Err(Error::Truncated { expected, actual }) => assert_eq!((expected, actual), (buf.len(), truncated.len())),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    let mut decoder = LimgDecoder::new(truncated)?;
+    let mut rows = vec![Pixel::BLACK; 300 * 100];
+    assert_eq!(decoder.read_rows(&mut rows)?, 100);
+    match decoder.read_rows(&mut rows) {
+        Err(Error::Truncated { expected, actual }) => assert_eq!((expected, actual), (buf.len(), truncated.len())),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    let recovered = Image::from_buffer_with_options(truncated, DecodeOptions::LENIENT)?;
+    assert_eq!(recovered.pixels()[..300 * 100], image.pixels()[..300 * 100]);
+    assert!(recovered.pixels()[300 * 100..].iter().all(|&p| p == Pixel::BLACK));
+    match Image::from_read(truncated) {
+        Err(Error::Truncated { expected, actual }) => assert_eq!((expected, actual), (buf.len(), truncated.len())),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    let path = std::env::temp_dir().join("limg_decode_options_test.limg");
+    std::fs::write(&path, truncated)?;
+    let opened = Image::open(&path);
+    std::fs::remove_file(&path)?;
+    match opened {
+        Err(Error::Truncated { expected, actual }) => assert_eq!((expected, actual), (buf.len(), truncated.len())),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    let recovered = Image::from_read_with_options(truncated, DecodeOptions::LENIENT)?;
+    assert_eq!(recovered.pixels()[..300 * 100], image.pixels()[..300 * 100]);
+
+    // 余分なデータ
+    let mut trailing = buf.clone();
+    trailing.extend_from_slice(b"garbage");
+    assert_eq!(Image::from_buffer(&trailing)?, image);
+    assert!(matches!(
+        Image::from_read_with_options(trailing.as_slice(), DecodeOptions::STRICT),
+        Err(Error::TrailingData { actual, .. }) if actual == buf.len() + 7
+    ));
+    assert_eq!(Image::from_buffer_with_options(&buf, DecodeOptions::STRICT)?, image);
+
+    // 圧縮された画像
+    let compressed = image.to_compressed(Compression::Rle16)?;
+    let half = &compressed[..compressed.len() / 2];
+    assert!(matches!(Image::from_buffer(half), Err(Error::Truncated { .. })));
+    let recovered = Image::from_buffer_with_options(half, DecodeOptions::LENIENT)?;
+    assert_eq!(recovered.pixels()[..300], image.pixels()[..300]);
+    assert_eq!(recovered.pixels()[300 * 300 - 1], Pixel::BLACK);
+
+    Ok(())
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mmap_test() -> Result<()> {
+    for name in ["black_white_alpha_le", "black_white_alpha_be", "green_be"] {
+        let path = format!("tests/limg/{name}.limg");
+        let image = Image::open(&path)?;
+        let mapped = unsafe { Image::open_mmap(&path)? };
+
+        let native = cfg!(target_endian = "little") == name.ends_with("_le");
+        assert_eq!(mapped.is_zero_copy(), native);
+        assert_eq!(mapped.pixels().is_some(), native);
+        if let Some(pixels) = mapped.pixels() {
+            assert_eq!(pixels, image.pixels());
+        }
+
+        assert_eq!(mapped.transparent_color(), image.transparent_color());
+        assert_eq!(mapped.get_pixel(150, 299), image.get_pixel(150, 299).copied());
+        assert_eq!(mapped.get_pixel(300, 0), None);
+        assert_eq!(mapped.row(120), image.row(120));
+        assert_eq!(mapped.to_image(), image);
+    }
+
+    let dir = std::env::temp_dir();
+    let image = Image::open("tests/limg/red_le.limg")?;
+    let path = dir.join("limg_mmap_test.limg");
+    image.save_compressed(&path, Compression::Rle16)?;
+    assert!(matches!(unsafe { Image::open_mmap(&path) }, Err(Error::UnsupportedFormat)));
+
+    let buf = std::fs::read("tests/limg/red_le.limg")?;
+    std::fs::write(&path, &buf[..buf.len() - 2])?;
+    assert!(matches!(unsafe { Image::open_mmap(&path) }, Err(Error::Truncated { .. })));
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}